@@ -1,24 +1,93 @@
 use convert_case::{self, Case, Casing};
 use proc_macro2::{Ident, Literal, Span, TokenStream};
 use quote::quote;
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::env;
 use std::fs;
+use std::io;
 use std::ops::Range;
 use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Path of the benchmark manifest.
+pub const MNF_PTH: &str = "benches.toml";
+
+/// Environment variable seeding random literals in generated code.
+pub const SEED_VAR: &str = "MTR_SEED";
+
+/// Seed of random literals when `MTR_SEED` isn't set.
+pub const SEED_DFT: u64 = 0;
+
+/// Environment variable listing the families of studies to generate, such as `alc,rd`.
+pub const FAM_VAR: &str = "MTR_FAMILIES";
 
 /// Runs the build script.
 fn main() -> std::io::Result<()> {
     println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed={}", MNF_PTH);
+    emit_bld_env();
+
+    let mnf = read_mnf(MNF_PTH)?;
+    write_all_files("./src/", &mnf)
+}
+
+/// Sets build metadata environment variables recorded with saved results.
+pub fn emit_bld_env() {
+    for pth in [".git/HEAD", ".git/refs/heads"] {
+        if Path::new(pth).exists() {
+            println!("cargo:rerun-if-changed={}", pth);
+        }
+    }
+    let git = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .output()
+        .ok()
+        .filter(|out| out.status.success())
+        .map(|out| String::from_utf8_lossy(&out.stdout).trim().to_string())
+        .unwrap_or_else(|| String::from("unknown"));
+    println!("cargo:rustc-env=MTR_GIT={}", git);
+
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| String::from("rustc"));
+    let rustc = Command::new(rustc)
+        .arg("--version")
+        .output()
+        .ok()
+        .filter(|out| out.status.success())
+        .map(|out| String::from_utf8_lossy(&out.stdout).trim().to_string())
+        .unwrap_or_else(|| String::from("unknown"));
+    println!("cargo:rustc-env=MTR_RUSTC={}", rustc);
+
+    println!("cargo:rerun-if-env-changed={}", SEED_VAR);
+    println!("cargo:rustc-env=MTR_BLD_SEED={}", bld_seed());
+}
+
+/// Returns the seed of random literals in generated code.
+pub fn bld_seed() -> u64 {
+    match env::var(SEED_VAR) {
+        Ok(val) => match val.trim().parse() {
+            Ok(seed) => seed,
+            Err(_) => panic!("{} must be a u64, not '{}'", SEED_VAR, val),
+        },
+        Err(_) => SEED_DFT,
+    }
+}
 
-    write_all_files("./src/")
+/// Returns the generator of random literals for a length.
+///
+/// Every study draws the same literals for a length, so compared variants read equal inputs.
+pub fn len_rng(len: u32) -> StdRng {
+    StdRng::seed_from_u64(bld_seed() ^ (len as u64).rotate_left(32))
 }
 
 /// Writes all files to a directory.
-pub fn write_all_files(dir: &str) -> std::io::Result<()> {
+pub fn write_all_files(dir: &str, mnf: &Mnf) -> std::io::Result<()> {
     let pth = Path::new(dir);
     fs::create_dir_all(pth)?;
 
-    write_one_fle(emit_main_fle(), &pth.join("main.rs"))?;
+    write_one_fle(emit_main_fle(mnf), &pth.join("main.rs"))?;
 
     Ok(())
 }
@@ -40,16 +109,15 @@ pub fn emit_imports() -> TokenStream {
         #![allow(dead_code)]
         #![allow(unused_imports)]
         use anyhow::{bail, Result};
-        use ben::*;
-        use ben::Sta::*;
         use itr::*;
+        use mtr::*;
         use rand::seq::SliceRandom;
-        use rand::thread_rng;
         use std::borrow::Borrow;
         use std::fmt;
         use std::hash::Hash;
-        use std::sync::Arc;
+        use std::sync::atomic::{AtomicU32, Ordering};
         use std::sync::mpsc::channel;
+        use std::sync::{Arc, Barrier};
         use std::thread::{self, JoinHandle};
         use threadpool::ThreadPool;
         use Lbl::*;
@@ -58,17 +126,20 @@ pub fn emit_imports() -> TokenStream {
     stm
 }
 
-pub fn emit_main_fle() -> TokenStream {
+pub fn emit_main_fle(mnf: &Mnf) -> TokenStream {
+    let mut stm = TokenStream::new();
+    stm.extend(emit_imports());
+    stm.extend(emit_main_fn());
+    stm.extend(emit_dft_qry_fn(mnf));
+    stm.extend(emit_new_stdy(mnf));
+
     let tok_fns = [
-        emit_imports,
-        emit_main_fn,
-        emit_new_stdy,
         emit_lbl_enum,
         emit_lbl_impl_display,
         emit_lbl_impl_enumstructval,
         emit_lbl_impl_label,
     ];
-    let ret = tok_fns.iter().fold(TokenStream::new(), |mut stm, tok_fn| {
+    let ret = tok_fns.iter().fold(stm, |mut stm, tok_fn| {
         stm.extend(tok_fn());
         stm
     });
@@ -76,7 +147,646 @@ pub fn emit_main_fle() -> TokenStream {
     ret
 }
 
+/// Benchmark manifest.
+#[derive(Debug, Deserialize)]
+pub struct Mnf {
+    /// Length ranges by name.
+    pub rng: BTreeMap<String, MnfRng>,
+    /// Bytes read per element by family label, such as `acm`.
+    #[serde(default)]
+    pub elm: BTreeMap<String, u32>,
+    /// Reusable snippets by name.
+    #[serde(default)]
+    pub snp: BTreeMap<String, String>,
+    /// Studies to register.
+    #[serde(default)]
+    pub stdy: Vec<MnfStdy>,
+    /// Grids of parallel studies, expanded into `stdy` when read.
+    #[serde(default)]
+    pub grd: Vec<MnfGrd>,
+    /// Sweeps of the accumulate family, expanded into `stdy` when read.
+    #[serde(default)]
+    pub acm_swp: Vec<MnfAcmSwp>,
+    /// Default selections.
+    #[serde(default)]
+    pub sel: Vec<MnfSel>,
+    /// Default comparisons.
+    #[serde(default)]
+    pub cmp: Vec<MnfCmp>,
+    /// Default rankings.
+    #[serde(default)]
+    pub rnk: Vec<MnfRnk>,
+    /// Default reports of the fastest series per length.
+    #[serde(default)]
+    pub bst: Vec<MnfSel>,
+    /// Family labels whose studies take their lengths at runtime, such as `acm`.
+    #[serde(default)]
+    pub rtm: Vec<String>,
+    /// Labels whose studies spawn threads inside the timed body, such as `spn`.
+    #[serde(default)]
+    pub spn: Vec<String>,
+}
+
+/// A length range declared in the benchmark manifest.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum MnfRng {
+    /// Powers of two over `[first exponent, end exponent)`.
+    Pow2(Range<u32>),
+    /// The union of generated sequences.
+    Seq(MnfSeq),
+}
+
+/// Generated length sequences of a manifest range.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MnfSeq {
+    /// Powers of two over `[first exponent, end exponent)`.
+    pub pow2: Option<Range<u32>>,
+    /// Offsets added to each power of two, such as `[-1, 1]`.
+    #[serde(default)]
+    pub ofs: Vec<i64>,
+    /// Linear steps `[first, end, step]`, with the end excluded.
+    pub lin: Option<[u32; 3]>,
+    /// Primes over `[first, end)`.
+    pub prm: Option<Range<u32>>,
+}
+
+impl MnfRng {
+    /// Returns the positive lengths of the range, sorted and without duplicates.
+    pub fn lens(&self) -> Vec<u32> {
+        let mut ret: Vec<u32> = match self {
+            MnfRng::Pow2(exps) => exps.clone().map(|x| 2u32.pow(x)).collect(),
+            MnfRng::Seq(seq) => {
+                let mut ret: Vec<u32> = Vec::new();
+                if let Some(exps) = &seq.pow2 {
+                    let ofss = if seq.ofs.is_empty() {
+                        vec![0]
+                    } else {
+                        seq.ofs.clone()
+                    };
+                    for exp in exps.clone() {
+                        ret.extend(
+                            ofss.iter()
+                                .filter_map(|ofs| u32::try_from(2i64.pow(exp) + ofs).ok()),
+                        );
+                    }
+                }
+                if let Some([fst, end, stp]) = seq.lin {
+                    if stp == 0 {
+                        panic!("{}: a linear range needs a positive step", MNF_PTH);
+                    }
+                    ret.extend((fst..end).step_by(stp as usize));
+                }
+                if let Some(prm) = &seq.prm {
+                    ret.extend(prm.clone().filter(|x| is_prm(*x)));
+                }
+                ret
+            }
+        };
+        ret.retain(|x| *x > 0);
+        ret.sort_unstable();
+        ret.dedup();
+        ret
+    }
+}
+
+/// Returns true when a number is prime.
+pub fn is_prm(x: u32) -> bool {
+    let x = x as u64;
+    x >= 2
+        && (2..)
+            .take_while(|d| d * d <= x)
+            .all(|d| !x.is_multiple_of(d))
+}
+
+/// A study declared in the benchmark manifest.
+#[derive(Debug, Deserialize)]
+pub struct MnfStdy {
+    /// Labels of the series, such as `acm(1)`.
+    pub lbls: Vec<String>,
+    /// Name of a length range.
+    pub rng: String,
+    /// Setup which runs before the timer starts.
+    pub stp: Option<String>,
+    /// Timed body.
+    pub bdy: String,
+    /// Value returned after the timer stops.
+    pub ret: Option<String>,
+    /// Whether the study takes its lengths at runtime, in place of its families in `rtm`.
+    pub rtm: Option<bool>,
+    /// Thread count from the CPU count at runtime, in place of a `thd` label.
+    #[serde(skip)]
+    pub ncpu: Option<MnfNcpu>,
+}
+
+/// A thread count of a generated study, a multiple of the CPU count at runtime.
+///
+/// Taking the count at runtime rather than from the build machine keeps generated code the
+/// same on every machine.
+#[derive(Debug, Clone)]
+pub struct MnfNcpu {
+    /// Multiple of the CPU count.
+    pub mlt: u32,
+    /// Fixed thread counts of the same studies, which the runtime count skips.
+    pub thd: Vec<u32>,
+}
+
+/// A grid of parallel studies declared in the benchmark manifest.
+///
+/// Registers one study per thread count and synchronization label, labelled with the grid
+/// labels, `thd(n)` and the synchronization label.
+#[derive(Debug, Deserialize)]
+pub struct MnfGrd {
+    /// Labels of every study in the grid, such as `acm(1)`.
+    pub lbls: Vec<String>,
+    /// Name of a length range.
+    pub rng: String,
+    /// Thread counts.
+    #[serde(default)]
+    pub thd: Vec<u32>,
+    /// Multiples of the CPU count at runtime added to the thread counts.
+    #[serde(default)]
+    pub ncpu: Vec<u32>,
+    /// Study templates by synchronization label, such as `mpsc`.
+    pub syn: BTreeMap<String, MnfSyn>,
+}
+
+/// A study template of one synchronization mechanism in a grid.
+///
+/// `#thd` is substituted with the thread count of each study.
+#[derive(Debug, Deserialize)]
+pub struct MnfSyn {
+    /// Setup which runs before the timer starts.
+    pub stp: Option<String>,
+    /// Timed body.
+    pub bdy: String,
+    /// Value returned after the timer stops.
+    pub ret: Option<String>,
+}
+
+/// A sweep of the accumulate family declared in the benchmark manifest.
+///
+/// Registers one study per accumulator count, unroll factor and thread count, where the
+/// accumulator count divides the unroll factor.
+#[derive(Debug, Deserialize)]
+pub struct MnfAcmSwp {
+    /// Labels added to every study of the sweep, such as `tl`.
+    #[serde(default)]
+    pub lbls: Vec<String>,
+    /// Name of a length range.
+    pub rng: String,
+    /// Accumulator counts.
+    pub acm: Vec<u32>,
+    /// Unroll factors.
+    pub unr: Vec<u32>,
+    /// Thread counts. Without thread counts, the sweep sums in place on one thread and its
+    /// studies have no `thd` label.
+    #[serde(default)]
+    pub thd: Vec<u32>,
+    /// Multiples of the CPU count at runtime added to the thread counts.
+    #[serde(default)]
+    pub ncpu: Vec<u32>,
+    /// Whether the studies take their lengths at runtime, in place of the `acm` family in `rtm`.
+    pub rtm: Option<bool>,
+}
+
+/// A selection declared in the benchmark manifest.
+#[derive(Debug, Deserialize)]
+pub struct MnfSel {
+    /// Labels to select.
+    pub lbls: Vec<String>,
+}
+
+/// A comparison declared in the benchmark manifest.
+#[derive(Debug, Deserialize)]
+pub struct MnfCmp {
+    /// Label selections to compare.
+    pub sels: Vec<Vec<String>>,
+}
+
+/// A ranking declared in the benchmark manifest.
+#[derive(Debug, Deserialize)]
+pub struct MnfRnk {
+    /// Label selections to rank.
+    pub sels: Vec<Vec<String>>,
+}
+
+/// Reads the benchmark manifest.
+pub fn read_mnf(pth: &str) -> io::Result<Mnf> {
+    let txt = fs::read_to_string(pth)?;
+    let mut mnf: Mnf = toml::from_str(&txt)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", pth, err)))?;
+
+    env_rngs(&mut mnf)?;
+    for (nam, rng) in mnf.rng.iter() {
+        if rng.lens().is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: range '{}' is empty", pth, nam),
+            ));
+        }
+    }
+
+    for nam in mnf.rtm.iter() {
+        if !lbl_strs_plain().contains(&nam.as_str())
+            && !lbl_strs_struct_u32().contains(&nam.as_str())
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: unknown runtime family '{}'", pth, nam),
+            ));
+        }
+    }
+    for nam in mnf.spn.iter() {
+        if !lbl_strs_plain().contains(&nam.as_str()) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: unknown spawning label '{}'", pth, nam),
+            ));
+        }
+    }
+
+    for grd in std::mem::take(&mut mnf.grd) {
+        mnf.stdy.extend(grd_stdys(&grd));
+    }
+    for swp in std::mem::take(&mut mnf.acm_swp) {
+        mnf.stdy.extend(acm_swp_stdys(&swp));
+    }
+    env_fams(&mut mnf)?;
+
+    Ok(mnf)
+}
+
+/// Returns the environment variable overriding a manifest range, such as `MTR_ALC_RANGE`.
+pub fn rng_var(nam: &str) -> String {
+    format!("MTR_{}_RANGE", nam.to_uppercase())
+}
+
+/// Replaces manifest ranges with those set in `MTR_<NAME>_RANGE`, written as in `[rng]`,
+/// such as `[4, 10]` or `{ pow2 = [4, 10], ofs = [-1, 1] }`.
+pub fn env_rngs(mnf: &mut Mnf) -> io::Result<()> {
+    #[derive(Deserialize)]
+    struct EnvRng {
+        rng: MnfRng,
+    }
+    for (nam, rng) in mnf.rng.iter_mut() {
+        let var = rng_var(nam);
+        println!("cargo:rerun-if-env-changed={}", var);
+        let Ok(val) = env::var(&var) else {
+            continue;
+        };
+        *rng = toml::from_str::<EnvRng>(&format!("rng = {}", val))
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", var, err)))?
+            .rng;
+    }
+    Ok(())
+}
+
+/// Keeps the studies of the families listed in `MTR_FAMILIES`, when set.
+///
+/// A study belongs to a family when any of its labels has the family's name, ignoring any
+/// value, so `acm` keeps the accumulate, sweep and parallel studies. Default selections,
+/// comparisons, rankings and best reports with a selection no kept study matches are
+/// dropped.
+pub fn env_fams(mnf: &mut Mnf) -> io::Result<()> {
+    println!("cargo:rerun-if-env-changed={}", FAM_VAR);
+    let Ok(val) = env::var(FAM_VAR) else {
+        return Ok(());
+    };
+    let err =
+        |msg: String| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", FAM_VAR, msg));
+    let fams: Vec<&str> = val
+        .split(',')
+        .map(str::trim)
+        .filter(|fam| !fam.is_empty())
+        .collect();
+    for fam in fams.iter() {
+        if !lbl_strs_plain().contains(fam) && !lbl_strs_struct_u32().contains(fam) {
+            return Err(err(format!("unknown family '{}'", fam)));
+        }
+    }
+    mnf.stdy
+        .retain(|stdy| stdy.lbls.iter().any(|lbl| fams.contains(&lbl_nam(lbl))));
+    if mnf.stdy.is_empty() {
+        return Err(err(format!("no study belongs to '{}'", val)));
+    }
+
+    let mat = |sel: &[String]| mnf.stdy.iter().any(|stdy| mnf_mat(sel, &stdy.lbls));
+    mnf.sel.retain(|sel| mat(&sel.lbls));
+    mnf.cmp.retain(|cmp| cmp.sels.iter().all(|sel| mat(sel)));
+    mnf.rnk.retain(|rnk| rnk.sels.iter().all(|sel| mat(sel)));
+    mnf.bst.retain(|bst| mat(&bst.lbls));
+    Ok(())
+}
+
+/// Returns the name of a manifest label, such as `acm` of `acm(1)`.
+pub fn lbl_nam(lbl: &str) -> &str {
+    lbl.split_once('(').map_or(lbl, |(nam, _)| nam)
+}
+
+/// Returns true when every label of a selection is among a study's labels, by name for a
+/// label without a value such as `acm`, like `sel_mat` at runtime.
+pub fn mnf_mat(sel: &[String], lbls: &[String]) -> bool {
+    sel.iter()
+        .all(|tok| lbls.iter().any(|lbl| lbl == tok || lbl_nam(lbl) == tok))
+}
+
+/// Returns counts sorted, without zeros and duplicates.
+pub fn cnts(cnts: &[u32]) -> Vec<u32> {
+    let mut ret: Vec<u32> = cnts.to_vec();
+    ret.retain(|x| *x > 0);
+    ret.sort_unstable();
+    ret.dedup();
+    ret
+}
+
+/// Returns the fixed thread counts of a grid.
+///
+/// Counts below two are skipped, since one thread is the baseline study of the family.
+pub fn grd_thds(grd: &MnfGrd) -> Vec<u32> {
+    let mut ret = cnts(&grd.thd);
+    ret.retain(|x| *x >= 2);
+    ret
+}
+
+/// Returns the studies of the accumulate sweep.
+///
+/// Each thread sums its share of the values with `acm` accumulators, `unr` values per
+/// step, and the remaining values one at a time. More than one thread dispatch to the
+/// persistent workers of `pol`, so the timer covers the kernel and not thread creation.
+pub fn acm_swp_stdys(swp: &MnfAcmSwp) -> Vec<MnfStdy> {
+    let mut ret: Vec<MnfStdy> = Vec::new();
+    // Fixed counts, then multiples of the CPU count at runtime, which always take the pool.
+    let fxd = cnts(&swp.thd);
+    let mut thds: Vec<(Option<u32>, Option<MnfNcpu>)> = Vec::new();
+    if swp.thd.is_empty() && swp.ncpu.is_empty() {
+        thds.push((None, None));
+    }
+    thds.extend(fxd.iter().map(|thd| (Some(*thd), None)));
+    for mlt in cnts(&swp.ncpu) {
+        let ncpu = MnfNcpu {
+            mlt,
+            thd: fxd.clone(),
+        };
+        thds.push((None, Some(ncpu)));
+    }
+    for (thd, ncpu) in thds {
+        for unr in swp.unr.iter().copied() {
+            for acm in swp
+                .acm
+                .iter()
+                .copied()
+                .filter(|acm| *acm > 0 && unr % acm == 0)
+            {
+                let (stp, bdy) = if ncpu.is_none() && thd.unwrap_or(1) == 1 {
+                    (
+                        format!(
+                            "#vals\nlet mut ret = [0u32; {}];\nlet mut n: usize = 0;\n",
+                            acm
+                        ),
+                        format!("{}let sum = {};\n", acm_krn(acm, unr, "#len"), acm_sum(acm)),
+                    )
+                } else {
+                    (
+                        format!(
+                            "#vals\n\
+                             let thd_cnt: usize = {};\n\
+                             let vals: Arc<Vec<u32>> = Arc::new(vals);\n\
+                             let idx_rngs = rngs(thd_cnt, vals.len())\
+                             .into_iter().collect::<Vec<_>>();\n\
+                             let pol = pol(thd_cnt);\n",
+                            thd.map_or(String::from("thd"), |thd| thd.to_string())
+                        ),
+                        format!(
+                            "let sums = pol.map({{\n\
+                             let vals = vals.clone();\n\
+                             let idx_rngs = idx_rngs.clone();\n\
+                             move |wkr| {{\n\
+                             let vals: &[u32] = &vals;\n\
+                             let rng = idx_rngs[wkr].clone();\n\
+                             let mut ret = [0u32; {}];\n\
+                             let mut n: usize = rng.start;\n\
+                             {}{}\n\
+                             }}\n\
+                             }});\n\
+                             let sum = sums.iter().sum::<u32>();\n",
+                            acm,
+                            acm_krn(acm, unr, "rng.end"),
+                            acm_sum(acm)
+                        ),
+                    )
+                };
+                let mut lbls = swp.lbls.clone();
+                lbls.extend([format!("acm({})", acm), format!("unr({})", unr)]);
+                lbls.extend(thd.map(|thd| format!("thd({})", thd)));
+                ret.push(MnfStdy {
+                    lbls,
+                    rng: swp.rng.clone(),
+                    stp: Some(stp),
+                    bdy,
+                    ret: Some(String::from("sum")),
+                    rtm: swp.rtm,
+                    ncpu: ncpu.clone(),
+                });
+            }
+        }
+    }
+    ret
+}
+
+/// Returns loops adding values from `n` to `end` into `acm` accumulators, `unr` per step,
+/// then the tail of fewer than `unr` values into the first accumulator.
+fn acm_krn(acm: u32, unr: u32, end: &str) -> String {
+    if unr == 1 {
+        return format!("while n < {} {{\nret[0] += vals[n];\nn += 1;\n}}\n", end);
+    }
+    let mut ret = format!("while n + {} <= {} {{\n", unr, end);
+    for idx in 0..unr {
+        match idx {
+            0 => ret.push_str("ret[0] += vals[n];\n"),
+            _ => ret.push_str(&format!("ret[{}] += vals[n + {}];\n", idx % acm, idx)),
+        }
+    }
+    ret.push_str(&format!("n += {};\n}}\n", unr));
+    ret.push_str(&format!(
+        "while n < {} {{\nret[0] += vals[n];\nn += 1;\n}}\n",
+        end
+    ));
+    ret
+}
+
+/// Returns the sum of `acm` accumulators.
+fn acm_sum(acm: u32) -> String {
+    (0..acm)
+        .map(|idx| format!("ret[{}]", idx))
+        .collect::<Vec<_>>()
+        .join(" + ")
+}
+
+/// Returns the studies of a grid, one per thread count and synchronization label.
+pub fn grd_stdys(grd: &MnfGrd) -> Vec<MnfStdy> {
+    let mut ret: Vec<MnfStdy> = Vec::new();
+    let fxd = grd_thds(grd);
+    for (syn, tpl) in grd.syn.iter() {
+        // Fixed counts, then multiples of the CPU count in the runtime variable `thd`.
+        let mut thds: Vec<(String, Option<MnfNcpu>)> = Vec::new();
+        thds.extend(fxd.iter().map(|thd| (thd.to_string(), None)));
+        for mlt in cnts(&grd.ncpu) {
+            let ncpu = MnfNcpu {
+                mlt,
+                thd: fxd.clone(),
+            };
+            thds.push((String::from("thd"), Some(ncpu)));
+        }
+        for (thd, ncpu) in thds {
+            let mut lbls = grd.lbls.clone();
+            if ncpu.is_none() {
+                lbls.push(format!("thd({})", thd));
+            }
+            lbls.push(syn.clone());
+            ret.push(MnfStdy {
+                lbls,
+                rng: grd.rng.clone(),
+                stp: tpl.stp.as_ref().map(|stp| sub_thd(stp, &thd)),
+                bdy: sub_thd(&tpl.bdy, &thd),
+                ret: tpl.ret.as_ref().map(|ret| sub_thd(ret, &thd)),
+                rtm: None,
+                ncpu,
+            });
+        }
+    }
+    ret
+}
+
+/// Returns a template with each `#thd` placeholder substituted with a thread count or the
+/// runtime variable `thd`.
+fn sub_thd(tpl: &str, thd: &str) -> String {
+    let mut ret = String::with_capacity(tpl.len());
+    let mut rst = tpl;
+    while let Some(idx) = rst.find("#thd") {
+        let (bfr, aft) = rst.split_at(idx);
+        let aft = &aft["#thd".len()..];
+        ret.push_str(bfr);
+        // Leave longer placeholders, such as `#thd_vals`, to snippet substitution.
+        match aft.chars().next() {
+            Some(chr) if chr.is_ascii_alphanumeric() || chr == '_' => ret.push_str("#thd"),
+            _ => ret.push_str(thd),
+        }
+        rst = aft;
+    }
+    ret.push_str(rst);
+    ret
+}
+
+/// Returns the lengths of a named manifest range.
+pub fn mnf_lens(mnf: &Mnf, nam: &str) -> Vec<u32> {
+    match mnf.rng.get(nam) {
+        Some(rng) => rng.lens(),
+        None => panic!("{}: unknown range '{}'", MNF_PTH, nam),
+    }
+}
+
+/// Returns a manifest snippet with placeholders substituted for a length.
+///
+/// `#len` is the length literal, `#arr` random u32 literals, `#arms` match arms returning
+/// the same random u32s, and `#name` the snippet `name`. Without a length, `#len` is the
+/// runtime length `len: usize`, and `#arr` and `#arms` have no literals to expand to.
+pub fn mnf_snp(mnf: &Mnf, snp: &str, len: Option<u32>) -> TokenStream {
+    let txt = sub_snp(mnf, snp, len, 0);
+    match txt.parse::<TokenStream>() {
+        Ok(stm) => stm,
+        Err(err) => panic!("{}: invalid snippet '{}': {}", MNF_PTH, snp, err),
+    }
+}
+
+fn sub_snp(mnf: &Mnf, snp: &str, len: Option<u32>, dpt: u8) -> String {
+    if dpt > 8 {
+        panic!("{}: snippets nest too deeply in '{}'", MNF_PTH, snp);
+    }
+    let mut ret = String::with_capacity(snp.len());
+    let mut chrs = snp.chars().peekable();
+    while let Some(chr) = chrs.next() {
+        if chr != '#' {
+            ret.push(chr);
+            continue;
+        }
+        let mut nam = String::new();
+        while let Some(chr) = chrs.peek() {
+            if chr.is_ascii_alphanumeric() || *chr == '_' {
+                nam.push(*chr);
+                chrs.next();
+            } else {
+                break;
+            }
+        }
+        match (nam.as_str(), len) {
+            ("len", Some(len)) => ret.push_str(&len.to_string()),
+            ("len", None) => ret.push_str("len"),
+            ("arr", Some(len)) => {
+                let mut rng = len_rng(len);
+                for _ in 0..len {
+                    ret.push_str(&format!("{}, ", rng.gen_range(0..u32::MAX)));
+                }
+            }
+            ("arms", Some(len)) => {
+                let mut rng = len_rng(len);
+                for idx in 0..len {
+                    ret.push_str(&format!("{} => {}, ", idx, rng.gen_range(0..u32::MAX)));
+                }
+            }
+            ("arr" | "arms", None) => {
+                panic!(
+                    "{}: '#{}' needs a length literal, not a runtime length",
+                    MNF_PTH, nam
+                )
+            }
+            _ => match mnf.snp.get(&nam) {
+                Some(inr) => ret.push_str(&sub_snp(mnf, inr, len, dpt + 1)),
+                None => panic!("{}: unknown placeholder '#{}'", MNF_PTH, nam),
+            },
+        }
+    }
+    ret
+}
+
+/// Returns the tokens of a manifest label, such as `acm(1)` to `Acm(1)`.
+pub fn mnf_lbl(lbl: &str) -> TokenStream {
+    let (nam, val) = match lbl.split_once('(') {
+        Some((nam, val)) => (nam, val.strip_suffix(')')),
+        None => (lbl, None),
+    };
+    let idn = Ident::new(nam.to_case(Case::Pascal).as_str(), Span::call_site());
+    if lbl_strs_struct_u32().contains(&nam) {
+        let val: u32 = match val.and_then(|val| val.parse().ok()) {
+            Some(val) => val,
+            None => panic!("{}: label '{}' needs a u32 value", MNF_PTH, lbl),
+        };
+        let lit = Literal::u32_unsuffixed(val);
+        quote! { #idn(#lit) }
+    } else if lbl_strs_plain().contains(&nam) && val.is_none() {
+        quote! { #idn }
+    } else {
+        panic!("{}: unknown label '{}'", MNF_PTH, lbl)
+    }
+}
+
+/// Returns the tokens of a list of manifest labels.
+pub fn mnf_lbls(lbls: &[String]) -> TokenStream {
+    let lbls = lbls.iter().map(|lbl| mnf_lbl(lbl));
+    quote! { #(#lbls),* }
+}
 
+/// Returns a label expression literal of manifest labels, where a struct label without a
+/// value, such as `acm`, matches any value.
+pub fn mnf_expr(lbls: &[String]) -> Literal {
+    for lbl in lbls.iter() {
+        if !lbl_strs_struct_u32().contains(&lbl.as_str()) {
+            mnf_lbl(lbl);
+        }
+    }
+    Literal::string(&lbls.join(","))
+}
 
 /// Returns label strings for all enum cases.
 pub fn lbl_strs_all() -> Vec<&'static str> {
@@ -88,9 +798,9 @@ pub fn lbl_strs_all() -> Vec<&'static str> {
 /// Returns label strings mapping to a plain enum cases.
 pub fn lbl_strs_plain() -> Vec<&'static str> {
     vec![
-        "add", "alc", "arr", "chk", "cnt", "cst", "idx", "into_itr", "itr", "join", "lop", "mat",
-        "mcr", "mpsc", "none", "one", "ptr", "rnd", "rd", "rsz", "seq", "slc", "u8", "unchk", "usize",
-        "val",  "vct",
+        "add", "alc", "arr", "atm", "chk", "cnt", "cst", "idx", "into_itr", "itr", "join", "lop",
+        "mat", "mcr", "mpsc", "none", "one", "pol", "ptr", "rnd", "rd", "rsz", "scp", "seq", "slc",
+        "spn", "tl", "u8", "unchk", "usize", "val", "vct",
     ]
 }
 /// Returns label strings which map to struct u32 cases of an enum.
@@ -163,13 +873,8 @@ pub fn emit_lbl_impl_display() -> TokenStream {
         let lit = Literal::string(tmp.as_str());
         let lit_alt = Literal::string(lbl_str);
         stm_3.extend(quote! {
-            #idn(x) => {
-                if f.alternate() {
-                    write!(f, #lit_alt)
-                } else {
-                    write!(f, #lit, x)
-                }
-            },
+            #idn(_) if f.alternate() => write!(f, #lit_alt),
+            #idn(x) => write!(f, #lit, x),
         });
     }
     stm_2.extend(quote! {
@@ -246,97 +951,29 @@ pub fn emit_main_fn() -> TokenStream {
     stm.extend(quote! {
         /// Runs a benchmark function analysis.
         pub fn main() -> Result<()> {
+            let cli = Cli::parse(std::env::args().skip(1))?;
+            set_seed(cli.seed);
+            set_lens(&cli.lens);
+            if cli.hlp {
+                println!("{}", USAGE);
+                return Ok(());
+            }
+            if let Cmd::Compare(bas, cur) = &cli.cmd {
+                return cmp_runs(bas, cur, cli.thr);
+            }
             let mut stdy = new_stdy()?;
-            let itr: u16 = 64;
-            let mut qry = QryBld::new();
-
-            // Allocation: array vs vector macro
-            let alc_arr_id = qry.sel(&[Alc, Arr]);
-            let alc_vct_mcr_id = qry.sel(&[Alc, Vct, Mcr]);
-            qry.cmp(alc_arr_id, alc_vct_mcr_id);
-            // Allocation: array vs vector capacity and resize
-            let alc_vct_rsz_id = qry.sel(&[Alc, Vct, Rsz]);
-            qry.cmp(alc_arr_id, alc_vct_rsz_id);
-            // Allocation: vector macro vs vector capacity and resize
-            qry.cmp(alc_vct_mcr_id, alc_vct_rsz_id);
-            // Lookup: Sequential: array vs match
-            let rd_seq_arr_id = qry.sel(&[Rd, Seq, Arr]);
-            let rd_seq_mat_id = qry.sel(&[Rd, Seq, Mat]);
-            qry.cmp(rd_seq_arr_id, rd_seq_mat_id);
-            // Lookup: Random: array vs match
-            let rd_rnd_arr_id = qry.sel(&[Rd, Rnd, Arr]);
-            let rd_rnd_mat_id = qry.sel(&[Rd, Rnd, Mat]);
-            qry.cmp(rd_rnd_arr_id, rd_rnd_mat_id);
-            // Iteration: range index bounds checked vs range index unchecked
-            let lop_idx_chk_id = qry.sel(&[Lop, Idx, Chk]);
-            let lop_idx_unchk_id = qry.sel(&[Lop, Idx, Unchk]);
-            qry.cmp(lop_idx_chk_id, lop_idx_unchk_id);
-            // Iteration: range index (bounds checked) vs iterator
-            let lop_vct_itr_id = qry.sel(&[Lop, Vct, Itr]);
-            qry.cmp(lop_idx_chk_id, lop_vct_itr_id);
-            // Iteration: Vector: iterator vs into iterator
-            let lop_vct_intoitr_id = qry.sel(&[Lop, Vct, IntoItr]);
-            qry.cmp(lop_vct_itr_id, lop_vct_intoitr_id);
-            // Iteration: Slice: iterator vs into iterator
-            let lop_slc_itr_id = qry.sel(&[Lop, Slc, Itr]);
-            let lop_slc_intoitr_id = qry.sel(&[Lop, Slc, IntoItr]);
-            qry.cmp(lop_slc_itr_id, lop_slc_intoitr_id);
-            // Cast: u8 vs usize
-            let cst_u8_id = qry.sel(&[Cst, U8]);
-            let cst_usize_id = qry.sel(&[Cst, Usize]);
-            qry.cmp(cst_u8_id, cst_usize_id);
-            // Accumulate: read pointer vs read de-referenced value
-            let acm_rd_ptr_id = qry.sel(&[Acm(1), Rd, Ptr]);
-            let acm_rd_val_id = qry.sel(&[Acm(1), Rd, Val]);
-            qry.cmp(acm_rd_ptr_id, acm_rd_val_id);
-            // Accumulate: total count vs multiple add one
-            let acm_add_cnt_id = qry.sel(&[Acm(1), Add, Cnt]);
-            let acm_add_one_id = qry.sel(&[Acm(1), Add, One]);
-            qry.cmp(acm_add_cnt_id, acm_add_one_id);
-            
-            // Accumulate: acm 1, unr 1, thd 1 vs acm 2, unr 2, thd 1
-            let acm1_unr1_thd1 = qry.sel(&[Acm(1), Unr(1), Thd(1)]);
-            let acm2_unr2_thd1 = qry.sel(&[Acm(2), Unr(2), Thd(1)]);
-            qry.cmp(acm1_unr1_thd1, acm2_unr2_thd1);
-
-            // Accumulate: acm 1, unr 1, thd 1 vs acm 1, unr 8, thd 1
-            let acm1_unr8_thd1 = qry.sel(&[Acm(1), Unr(8), Thd(1)]);
-            qry.cmp(acm1_unr1_thd1, acm1_unr8_thd1);
-
-            // Accumulate: unr 1, var 1 vs unr 8, var 8
-            let acm8_unr8_thd1 = qry.sel(&[Acm(8), Unr(8), Thd(1)]);
-            qry.cmp(acm1_unr1_thd1, acm8_unr8_thd1);
-
-            // Accumulate: unr 8, var 8 vs unr 16, var 16
-            let acm16_unr16_thd1 = qry.sel(&[Acm(16), Unr(16), Thd(1)]);
-            qry.cmp(acm8_unr8_thd1, acm16_unr16_thd1);
-
-            // Accumulate: unr 1, var 1 vs unr 16, var 16
-            qry.cmp(acm1_unr1_thd1, acm16_unr16_thd1);
-
-            // Accumulate: acm 1, unr 1, thd 2, join vs acm 1, unr 1, thd 2, mpsc
-            let acm1_unr1_thd2_join = qry.sel(&[Acm(1), Unr(1), Thd(2), Join]);
-            let acm1_unr1_thd2_mpsc = qry.sel(&[Acm(1), Unr(1), Thd(2), Mpsc]);
-            qry.cmp(acm1_unr1_thd2_join, acm1_unr1_thd2_mpsc);
-
-            // Accumulate: acm 1, unr 1, thd 2, mpsc vs acm 1, unr 1, thd 4, mpsc
-            let acm1_unr1_thd4_mpsc = qry.sel(&[Acm(1), Unr(1), Thd(4), Mpsc]);
-            qry.cmp(acm1_unr1_thd2_mpsc, acm1_unr1_thd4_mpsc);
-
-            // Accumulate: acm 1, unr 1, thd 4, mpsc vs acm 1, unr 1, thd 8, mpsc
-            let acm1_unr1_thd8_mpsc = qry.sel(&[Acm(1), Unr(1), Thd(8), Mpsc]);
-            qry.cmp(acm1_unr1_thd4_mpsc, acm1_unr1_thd8_mpsc);
-
-            // Accumulate: acm 1, unr 1, thd 8, mpsc vs acm 1, unr 1, thd 16, mpsc
-            let acm1_unr1_thd16_mpsc = qry.sel(&[Acm(1), Unr(1), Thd(16), Mpsc]);
-            qry.cmp(acm1_unr1_thd8_mpsc, acm1_unr1_thd16_mpsc);
-
-            qry.cmp(acm1_unr1_thd1, acm1_unr1_thd2_mpsc);
-            qry.cmp(acm1_unr1_thd1, acm1_unr1_thd4_mpsc);
-            qry.cmp(acm1_unr1_thd1, acm1_unr1_thd8_mpsc);
-            qry.cmp(acm1_unr1_thd1, acm1_unr1_thd16_mpsc);
-            
-            stdy.run(qry, itr)?;
+            if cli.lst {
+                stdy.lst();
+                return Ok(());
+            }
+            let qry = if cli.is_dft() { dft_qry()? } else { cli.qry()? };
+            pin(cli.cpu, &cli.wkr_cpus)?;
+            set_ctr(cli.ctr);
+            set_unt(cli.unt);
+            let ress = stdy.run(qry, cli.itr, cli.adp())?;
+            let run = Run::new(&ress, cli.itr, cli.seed, cli.adp());
+            let (pth_json, pth_csv) = run.sav(&cli.out)?;
+            println!("results: {} {}", pth_json.display(), pth_csv.display());
             Ok(())
         }
     });
@@ -344,1197 +981,226 @@ pub fn emit_main_fn() -> TokenStream {
     stm
 }
 
-pub fn emit_new_stdy() -> TokenStream {
+pub fn emit_dft_qry_fn(mnf: &Mnf) -> TokenStream {
     let mut stm = TokenStream::new();
 
-    let idn_lbl = Ident::new(LBL_NAM, Span::call_site());
-
-    // fn: start
-    stm.extend(quote! {
-        /// Returns a study with registered benchmark functions.
-        pub fn new_stdy() -> Result<Stdy<#idn_lbl>>
-    });
-
     // fn: inner
     let mut stm_inr = TokenStream::new();
-    let tok_bens = [
-        emit_alc_arr,
-        emit_alc_vct_mcr,
-        emit_alc_vct_rsz,
-        emit_rd_seq_arr,
-        emit_rd_seq_mat,
-        emit_rd_rnd_arr,
-        emit_rd_rnd_mat,
-        emit_lop_idx_chk,
-        emit_lop_idx_unchk,
-        emit_lop_vec_itr,
-        emit_lop_vec_into_itr,
-        emit_lop_slc_itr,
-        emit_lop_slc_into_itr,
-        emit_cst_u8,
-        emit_cst_usize,
-        emit_acm_rd_ptr,
-        emit_acm_rd_val,
-        emit_acm_add_cnt,
-        emit_acm_add_one,
-        emit_acm1_unr1_thd1,
-        emit_acm2_unr2_thd1,
-        emit_acm1_unr8_thd1,
-        emit_acm8_unr8_thd1,
-        emit_acm16_unr16_thd1,
-        emit_acm1_unr1_thd2_join,
-        emit_acm1_unr1_thd2_mpsc,
-        emit_acm1_unr1_thd4_mpsc,
-        emit_acm1_unr1_thd8_mpsc,
-        emit_acm1_unr1_thd16_mpsc,
-    ];
-    tok_bens
-        .iter()
-        .for_each(|tok_ben| stm_inr.extend(tok_ben()));
-
-    // fn: end
-    stm.extend(quote! {
-        {
-            let mut stdy = Stdy::new();
-            #stm_inr
-            Ok(stdy)
+    for sel in mnf.sel.iter() {
+        let lbls = mnf_lbls(&sel.lbls);
+        stm_inr.extend(quote! {
+            qry.sel(&[#lbls]);
+        });
+    }
+    for cmp in mnf.cmp.iter() {
+        if cmp.sels.len() != 2 {
+            panic!("{}: a comparison needs two selections", MNF_PTH);
         }
-    });
-
-    stm
-}
-
-pub static ALC_RNG: Range<u32> = 4..18;
-
-pub fn emit_alc_arr() -> TokenStream {
-    let mut stm = TokenStream::new();
-
-    // sec: inner
-    let mut stm_inr = TokenStream::new();
-    for len in ALC_RNG.clone().map(|x| 2u32.pow(x)) {
-        let lit_len = Literal::u32_unsuffixed(len);
+        let lbls_a = mnf_lbls(&cmp.sels[0]);
+        let lbls_b = mnf_lbls(&cmp.sels[1]);
         stm_inr.extend(quote! {
-            x.ins(Len(#lit_len), || [0u32; #lit_len]);
+            let sel_a = qry.sel(&[#lbls_a]);
+            let sel_b = qry.sel(&[#lbls_b]);
+            qry.cmp(sel_a, sel_b);
         });
     }
-
-    // sec: end
-    stm.extend(quote! {
-        stdy.reg_bld(&[Alc, Arr], |x| {
-            #stm_inr
+    for rnk in mnf.rnk.iter() {
+        if rnk.sels.len() < 2 {
+            panic!("{}: a ranking needs two or more selections", MNF_PTH);
+        }
+        let sels = rnk.sels.iter().map(|sel| {
+            let lbls = mnf_lbls(sel);
+            quote! { qry.sel(&[#lbls]) }
         });
-    });
-
-    stm
-}
-
-pub fn emit_alc_vct_mcr() -> TokenStream {
-    let mut stm = TokenStream::new();
-
-    // sec: inner
-    let mut stm_inr = TokenStream::new();
-    for len in ALC_RNG.clone().map(|x| 2u32.pow(x)) {
-        let lit_len = Literal::u32_unsuffixed(len);
         stm_inr.extend(quote! {
-            x.ins(Len(#lit_len), || vec![0u32; #lit_len]);
+            let sels = [#(#sels),*];
+            qry.rnk(&sels);
+        });
+    }
+    for bst in mnf.bst.iter() {
+        let expr = mnf_expr(&bst.lbls);
+        stm_inr.extend(quote! {
+            let sel = qry.sel_str(#expr)?;
+            qry.bst(sel);
         });
     }
 
-    // sec: end
     stm.extend(quote! {
-        stdy.reg_bld(&[Alc, Vct, Mcr], |x| {
+        /// Returns the default query run when no selections are given on the command-line.
+        pub fn dft_qry() -> Result<QryBld> {
+            let mut qry = QryBld::new();
             #stm_inr
-        });
+            Ok(qry)
+        }
     });
 
     stm
 }
 
-pub fn emit_alc_vct_rsz() -> TokenStream {
+pub fn emit_new_stdy(mnf: &Mnf) -> TokenStream {
     let mut stm = TokenStream::new();
 
-    // sec: inner
+    let idn_lbl = Ident::new(LBL_NAM, Span::call_site());
+
+    // fn: start
+    // Generated bodies keep the layout of `prettyplease`, which rustfmt would rewrap.
+    stm.extend(quote! {
+        /// Returns a study with registered benchmark functions.
+        #[rustfmt::skip]
+        pub fn new_stdy() -> Result<Stdy<#idn_lbl>>
+    });
+
+    // fn: inner
     let mut stm_inr = TokenStream::new();
-    for len in ALC_RNG.clone().map(|x| 2u32.pow(x)) {
-        let lit_len = Literal::u32_unsuffixed(len);
-        stm_inr.extend(quote! {
-            x.ins(Len(#lit_len), || {
-                let mut ret = Vec::<u32>::with_capacity(#lit_len);
-                ret.resize(#lit_len, 0);
-                ret
-            });
-        });
-    }
+    mnf.stdy
+        .iter()
+        .for_each(|mnf_stdy| stm_inr.extend(emit_reg_bld(mnf, mnf_stdy)));
 
-    // sec: end
+    // fn: end
     stm.extend(quote! {
-        stdy.reg_bld(&[Alc, Vct, Rsz], |x| {
+        {
+            let mut stdy = Stdy::new();
             #stm_inr
-        });
+            Ok(stdy)
+        }
     });
 
     stm
 }
 
-pub static RD_RNG: Range<u32> = 4..12;
-
-pub fn emit_rd_seq_arr() -> TokenStream {
+/// Returns a `stdy.reg_bld` call for a manifest study.
+pub fn emit_reg_bld(mnf: &Mnf, mnf_stdy: &MnfStdy) -> TokenStream {
     let mut stm = TokenStream::new();
 
     // sec: inner
+    // Studies of a runtime family register one function per length from `rtm_lens`, with
+    // the manifest lengths as the default.
+    let rtm = mnf_stdy.rtm.unwrap_or_else(|| {
+        mnf_stdy
+            .lbls
+            .iter()
+            .any(|lbl| mnf.rtm.iter().any(|rtm| rtm == lbl_nam(lbl)))
+    });
+    let lens = mnf_lens(mnf, &mnf_stdy.rng);
     let mut stm_inr = TokenStream::new();
-    for len in RD_RNG.clone().map(|x| 2u32.pow(x)) {
-        // Create an array with random elements.
-        let mut stm_arr = TokenStream::new();
-        let mut rng = rand::thread_rng();
-        for _ in 0..len {
-            let lit_ret_n = Literal::u32_unsuffixed(rng.gen_range(0..u32::MAX));
-            stm_arr.extend(quote! { #lit_ret_n, });
-        }
-
-        // Read each element from an array in sequence.
-        let lit_len = Literal::u32_unsuffixed(len);
+    if rtm {
+        let ins = emit_ins(mnf, mnf_stdy, None);
+        let lit_lens = lens
+            .iter()
+            .map(|len| Literal::usize_unsuffixed(*len as usize));
         stm_inr.extend(quote! {
-            x.ins(Len(#lit_len), || {
-                let arr = [#stm_arr];
-                let mut ret = [0u32; 1];
-                for idx in 0..#lit_len {
-                    ret[0] = arr[idx];
-                }
-                ret[0]
-            });
+            for len in rtm_lens(&[#(#lit_lens),*]) {
+                #ins
+            }
         });
+    } else {
+        for len in lens {
+            stm_inr.extend(emit_ins(mnf, mnf_stdy, Some(len)));
+        }
     }
 
-    // sec: end
-    stm.extend(quote! {
-        stdy.reg_bld(&[Rd, Seq, Arr], |x| {
-            #stm_inr
-        });
-    });
-
-    stm
-}
+    // sec: element size
+    // The first label with a size in `[elm]`, ignoring any value such as `(1)`.
+    let elm = mnf_stdy
+        .lbls
+        .iter()
+        .find_map(|lbl| mnf.elm.get(lbl_nam(lbl)));
+    let stm_elm = match elm {
+        Some(elm) => {
+            let lit_elm = Literal::u32_unsuffixed(*elm);
+            quote! { x.elm(#lit_elm); }
+        }
+        None => TokenStream::new(),
+    };
 
-pub fn emit_rd_seq_mat() -> TokenStream {
-    let mut stm = TokenStream::new();
+    // sec: thread spawns
+    let spn = mnf_stdy
+        .lbls
+        .iter()
+        .any(|lbl| mnf.spn.iter().any(|spn| spn == lbl_nam(lbl)));
+    let stm_spn = match spn {
+        true => quote! { x.spn(); },
+        false => TokenStream::new(),
+    };
 
-    // sec: inner
-    let mut stm_inr = TokenStream::new();
-    let mut rng = rand::thread_rng();
-    for len in RD_RNG.clone().map(|x| 2u32.pow(x)) {
-        // Create match arms which return a random u32.
-        let mut stm_arm = TokenStream::new();
-        for idx in 0..len {
-            let lit_idx = Literal::u32_unsuffixed(idx);
-            let lit_ret_n = Literal::u32_unsuffixed(rng.gen_range(0..u32::MAX));
-            stm_arm.extend(quote! {
-                #lit_idx => #lit_ret_n,
+    // sec: end
+    let lbls = mnf_lbls(&mnf_stdy.lbls);
+    match &mnf_stdy.ncpu {
+        None => stm.extend(quote! {
+            stdy.reg_bld(&[#lbls], |x| {
+                #stm_elm
+                #stm_spn
+                #stm_inr
             });
-        }
-
-        // Read each element from a match in sequence.
-        let lit_len = Literal::u32_unsuffixed(len);
-        stm_inr.extend(quote! {
-            x.ins(Len(#lit_len), || {
-                let mut ret = [0u32; 1];
-                for idx in 0..#lit_len {
-                    ret[0] = match idx {
-                        #stm_arm
-                        _ => panic!("uh oh, no no: beyond the match limit"),
-                    }
+        }),
+        // A thread count from the CPU count at runtime, skipped when a fixed count has it.
+        Some(ncpu) => {
+            let lit_mlt = Literal::usize_unsuffixed(ncpu.mlt as usize);
+            let lit_thds = ncpu
+                .thd
+                .iter()
+                .map(|thd| Literal::usize_unsuffixed(*thd as usize));
+            stm.extend(quote! {
+                if let Some(thd) = ncpu_thd(#lit_mlt, &[#(#lit_thds),*]) {
+                    stdy.reg_bld(&[#lbls, Thd(thd as u32)], |x| {
+                        #stm_elm
+                        #stm_spn
+                        #stm_inr
+                    });
                 }
-                ret[0]
             });
-        });
+        }
     }
 
-    // sec: end
-    stm.extend(quote! {
-        stdy.reg_bld(&[Rd, Seq, Mat], |x| {
-            #stm_inr
-        });
-    });
-
     stm
-
 }
 
-pub fn emit_rd_rnd_arr() -> TokenStream {
-    let mut stm = TokenStream::new();
-
-    // sec: inner
-    let mut stm_inr = TokenStream::new();
-    let mut rng = rand::thread_rng();
-    for len in RD_RNG.clone().map(|x| 2u32.pow(x)) {
-        // Create an array with random elements.
-        let mut stm_arr = TokenStream::new();
-        for _ in 0..len {
-            let lit_ret_n = Literal::u32_unsuffixed(rng.gen_range(0..u32::MAX));
-            stm_arr.extend(quote! { #lit_ret_n, });
+/// Returns the insertion of a study's function for a length literal, or for the runtime
+/// length `len` without one.
+pub fn emit_ins(mnf: &Mnf, mnf_stdy: &MnfStdy, len: Option<u32>) -> TokenStream {
+    let (lbl_len, mve) = match len {
+        Some(len) => {
+            let lit_len = Literal::u32_unsuffixed(len);
+            (quote! { Len(#lit_len) }, TokenStream::new())
         }
-
-        // Read each element from an array in sequence.
-        let lit_len = Literal::u32_unsuffixed(len);
-        stm_inr.extend(quote! {
-            x.ins_prm(Len(#lit_len), |tme| {
-                let arr = [#stm_arr];
-                let mut idxs: Vec<usize> = (0..#lit_len).collect();
-                let mut rng = thread_rng();
-                idxs.shuffle(&mut rng);
-                let mut ret = [0u32; 1];
-                tme.borrow_mut().start();
-                for idx in idxs {
-                    ret[0] = arr[idx];
-                }
-                tme.borrow_mut().stop();
-                ret[0]
-            });
-        });
-    }
-
-    // sec: end
-    stm.extend(quote! {
-        stdy.reg_bld(&[Rd, Rnd, Arr], |x| {
-            #stm_inr
-        });
-    });
-
-    stm
-}
-
-pub fn emit_rd_rnd_mat() -> TokenStream {
-    let mut stm = TokenStream::new();
-
-    // sec: inner
-    let mut stm_inr = TokenStream::new();
-    let mut rng = rand::thread_rng();
-    for len in RD_RNG.clone().map(|x| 2u32.pow(x)) {
-        // Create match arms which return a random u32.
-        let mut stm_arm = TokenStream::new();
-        for idx in 0..len {
-            let lit_idx = Literal::u32_unsuffixed(idx);
-            let lit_ret_n = Literal::u32_unsuffixed(rng.gen_range(0..u32::MAX));
-            stm_arm.extend(quote! {
-                #lit_idx => #lit_ret_n,
-            });
+        None => (quote! { Len(len as u32) }, quote! { move }),
+    };
+    // Functions of a runtime thread count capture it.
+    let mve = match mnf_stdy.ncpu {
+        Some(_) => quote! { move },
+        None => mve,
+    };
+    let bdy = mnf_snp(mnf, &mnf_stdy.bdy, len);
+    match &mnf_stdy.stp {
+        None => {
+            // Keep single expression bodies, such as `[0u32; 16]`, unbraced.
+            let bdy = match syn::parse2::<syn::Expr>(bdy.clone()) {
+                Ok(_) => bdy,
+                Err(_) => quote! { { #bdy } },
+            };
+            quote! {
+                x.ins(#lbl_len, #mve || #bdy);
+            }
+        }
+        Some(stp) => {
+            let stp = mnf_snp(mnf, stp, len);
+            let ret = match &mnf_stdy.ret {
+                Some(ret) => mnf_snp(mnf, ret, len),
+                None => panic!(
+                    "{}: study '{}' has a setup without a return",
+                    MNF_PTH,
+                    mnf_stdy.lbls.join(",")
+                ),
+            };
+            quote! {
+                x.ins_prm(#lbl_len, #mve |tme| {
+                    #stp
+                    tme.borrow_mut().start();
+                    #bdy
+                    tme.borrow_mut().stop();
+                    #ret
+                });
+            }
         }
-
-        // Read each element from an array in sequence.
-        let lit_len = Literal::u32_unsuffixed(len);
-        stm_inr.extend(quote! {
-            x.ins_prm(Len(#lit_len), |tme| {
-                let mut idxs: Vec<usize> = (0..#lit_len).collect();
-                let mut rng = thread_rng();
-                idxs.shuffle(&mut rng);
-                let mut ret = [0u32; 1];
-                tme.borrow_mut().start();
-                for idx in idxs {
-                    ret[0] = match idx {
-                        #stm_arm
-                        _ => panic!("uh oh, no no: beyond the match limit"),
-                    }
-                }
-                tme.borrow_mut().stop();
-                ret[0]
-            });
-        });
-    }
-
-    // sec: end
-    stm.extend(quote! {
-        stdy.reg_bld(&[Rd, Rnd, Mat], |x| {
-            #stm_inr
-        });
-    });
-
-    stm
-
-}
-
-pub static LOP_RNG: Range<u32> = 4..18;
-
-pub fn emit_lop_idx_chk() -> TokenStream {
-    let mut stm = TokenStream::new();
-
-    // sec: inner
-    let mut stm_inr = TokenStream::new();
-    for len in LOP_RNG.clone().map(|x| 2u32.pow(x)) {
-        // Read each element from an array in sequence.
-        let lit_len = Literal::u32_unsuffixed(len);
-        stm_inr.extend(quote! {
-            x.ins_prm(Len(#lit_len), |tme| {
-                let mut vals: Vec<u32> = (0u32..#lit_len).collect();
-                let mut rng = thread_rng();
-                vals.shuffle(&mut rng);
-                let mut ret = [0u32; 1];
-                tme.borrow_mut().start();
-                for idx in 0..#lit_len {
-                    ret[0] = vals[idx];
-                }
-                tme.borrow_mut().stop();
-                ret[0]
-            });
-        });
-    }
-
-    // sec: end
-    stm.extend(quote! {
-        stdy.reg_bld(&[Lop, Idx, Chk], |x| {
-            #stm_inr
-        });
-    });
-
-    stm
-}
-
-pub fn emit_lop_idx_unchk() -> TokenStream {
-    let mut stm = TokenStream::new();
-
-    // sec: inner
-    let mut stm_inr = TokenStream::new();
-    for len in LOP_RNG.clone().map(|x| 2u32.pow(x)) {
-        // Iterate a for loop with range syntax 0..len.
-        let lit_len = Literal::u32_unsuffixed(len);
-        stm_inr.extend(quote! {
-            x.ins_prm(Len(#lit_len), |tme| {
-                let mut vals: Vec<u32> = (0u32..#lit_len).collect();
-                let mut rng = thread_rng();
-                vals.shuffle(&mut rng);
-                let mut ret = [0u32; 1];
-                tme.borrow_mut().start();
-                unsafe {
-                    for idx in 0..#lit_len {
-                        ret[0] = *vals.get_unchecked(idx);
-                    }
-                }
-                tme.borrow_mut().stop();
-                ret[0]
-            });
-        });
-    }
-
-    // sec: end
-    stm.extend(quote! {
-        stdy.reg_bld(&[Lop, Idx, Unchk], |x| {
-            #stm_inr
-        });
-    });
-
-    stm
-}
-
-pub fn emit_lop_vec_itr() -> TokenStream {
-    let mut stm = TokenStream::new();
-
-    // sec: inner
-    let mut stm_inr = TokenStream::new();
-    for len in LOP_RNG.clone().map(|x| 2u32.pow(x)) {
-        // Iterate a for loop with range syntax 0..len.
-        let lit_len = Literal::u32_unsuffixed(len);
-        stm_inr.extend(quote! {
-            x.ins_prm(Len(#lit_len), |tme| {
-                let mut vals: Vec<u32> = (0u32..#lit_len).collect();
-                let mut rng = thread_rng();
-                vals.shuffle(&mut rng);
-                let mut ret = [0u32; 1];
-                tme.borrow_mut().start();
-                for val in vals.iter() {
-                    ret[0] = *val;
-                }
-                tme.borrow_mut().stop();
-                ret[0]
-            });
-        });
-    }
-
-    // sec: end
-    stm.extend(quote! {
-        stdy.reg_bld(&[Lop, Itr, Vct], |x| {
-            #stm_inr
-        });
-    });
-
-    stm
-}
-
-pub fn emit_lop_vec_into_itr() -> TokenStream {
-    let mut stm = TokenStream::new();
-
-    // sec: inner
-    let mut stm_inr = TokenStream::new();
-    for len in LOP_RNG.clone().map(|x| 2u32.pow(x)) {
-        // Iterate a for loop with range syntax 0..len.
-        let lit_len = Literal::u32_unsuffixed(len);
-        stm_inr.extend(quote! {
-            x.ins_prm(Len(#lit_len), |tme| {
-                let mut vals: Vec<u32> = (0u32..#lit_len).collect();
-                let mut rng = thread_rng();
-                vals.shuffle(&mut rng);
-                let mut ret = [0u32; 1];
-                tme.borrow_mut().start();
-                for val in vals.into_iter() {
-                    ret[0] = val;
-                }
-                tme.borrow_mut().stop();
-                ret[0]
-            });
-        });
-    }
-
-    // sec: end
-    stm.extend(quote! {
-        stdy.reg_bld(&[Lop, IntoItr, Vct], |x| {
-            #stm_inr
-        });
-    });
-
-    stm
-}
-
-pub fn emit_lop_slc_itr() -> TokenStream {
-    let mut stm = TokenStream::new();
-
-    // sec: inner
-    let mut stm_inr = TokenStream::new();
-    for len in LOP_RNG.clone().map(|x| 2u32.pow(x)) {
-        // Iterate a for loop with range syntax 0..len.
-        let lit_len = Literal::u32_unsuffixed(len);
-        stm_inr.extend(quote! {
-            x.ins_prm(Len(#lit_len), |tme| {
-                let mut vals: Vec<u32> = (0u32..#lit_len).collect();
-                let mut rng = thread_rng();
-                vals.shuffle(&mut rng);
-                let mut ret = [0u32; 1];
-                tme.borrow_mut().start();
-                for val in vals.as_slice().iter() {
-                    ret[0] = *val;
-                }
-                tme.borrow_mut().stop();
-                ret[0]
-            });
-        });
-    }
-
-    // sec: end
-    stm.extend(quote! {
-        stdy.reg_bld(&[Lop, Slc, Itr], |x| {
-            #stm_inr
-        });
-    });
-
-    stm
-}
-
-pub fn emit_lop_slc_into_itr() -> TokenStream {
-    let mut stm = TokenStream::new();
-
-    // sec: inner
-    let mut stm_inr = TokenStream::new();
-    for len in LOP_RNG.clone().map(|x| 2u32.pow(x)) {
-        // Iterate a for loop with range syntax 0..len.
-        let lit_len = Literal::u32_unsuffixed(len);
-        stm_inr.extend(quote! {
-            x.ins_prm(Len(#lit_len), |tme| {
-                let mut vals: Vec<u32> = (0u32..#lit_len).collect();
-                let mut rng = thread_rng();
-                vals.shuffle(&mut rng);
-                let mut ret = [0u32; 1];
-                tme.borrow_mut().start();
-                for val in vals.as_slice().into_iter() {
-                    ret[0] = *val;
-                }
-                tme.borrow_mut().stop();
-                ret[0]
-            });
-        });
-    }
-
-    // sec: end
-    stm.extend(quote! {
-        stdy.reg_bld(&[Lop, Slc, IntoItr], |x| {
-            #stm_inr
-        });
-    });
-
-    stm
-}
-
-
-pub static CST_RNG: Range<u32> = 4..18;
-
-pub fn emit_cst_u8() -> TokenStream {
-    let mut stm = TokenStream::new();
-
-    // sec: inner
-    let mut stm_inr = TokenStream::new();
-    for len in CST_RNG.clone().map(|x| 2u32.pow(x)) {
-        let lit_len = Literal::u32_unsuffixed(len);
-        stm_inr.extend(quote! {
-            x.ins_prm(Len(#lit_len), |tme| {
-                let mut vals: Vec<u32> = (0u32..#lit_len).collect();
-                let mut rng = thread_rng();
-                vals.shuffle(&mut rng);
-                let mut ret = [0usize; 1];
-                tme.borrow_mut().start();
-                for val in vals.iter() {
-                    ret[0] += ((*val > 0xFF) as u8 + (*val > 0xFFFF) as u8 + (*val > 0xFFFFFF) as u8) as usize;
-                }
-                tme.borrow_mut().stop();
-                ret[0]
-            });
-        });
-    }
-
-    // sec: end
-    stm.extend(quote! {
-        stdy.reg_bld(&[Cst, U8], |x| {
-            #stm_inr
-        });
-    });
-
-    stm
-}
-
-pub fn emit_cst_usize() -> TokenStream {
-    let mut stm = TokenStream::new();
-
-    // sec: inner
-    let mut stm_inr = TokenStream::new();
-    for len in CST_RNG.clone().map(|x| 2u32.pow(x)) {
-        let lit_len = Literal::u32_unsuffixed(len);
-        stm_inr.extend(quote! {
-            x.ins_prm(Len(#lit_len), |tme| {
-                let mut vals: Vec<u32> = (0u32..#lit_len).collect();
-                let mut rng = thread_rng();
-                vals.shuffle(&mut rng);
-                let mut ret = [0usize; 1];
-                tme.borrow_mut().start();
-                for val in vals.iter() {
-                    ret[0] += (*val > 0xFF) as usize + (*val > 0xFFFF) as usize + (*val > 0xFFFFFF) as usize;
-                }
-                tme.borrow_mut().stop();
-                ret[0]
-            });
-        });
-    }
-
-    // sec: end
-    stm.extend(quote! {
-        stdy.reg_bld(&[Cst, Usize], |x| {
-            #stm_inr
-        });
-    });
-
-    stm
-}
-
-pub static ACM_RNG: Range<u32> = 4..18;
-
-pub fn emit_acm_rd_ptr() -> TokenStream {
-    let mut stm = TokenStream::new();
-
-    // sec: inner
-    let mut stm_inr = TokenStream::new();
-    for len in ACM_RNG.clone().map(|x| 2u32.pow(x)) {
-        let lit_len = Literal::u32_unsuffixed(len);
-        stm_inr.extend(quote! {
-            x.ins_prm(Len(#lit_len), |tme| {
-                let mut vals: Vec<u32> = (0u32..#lit_len).collect();
-                let mut rng = thread_rng();
-                vals.shuffle(&mut rng);
-                let mut ret = [0usize; 1];
-                tme.borrow_mut().start();
-                for val in vals.iter() {
-                    ret[0] += (*val > 0xFF) as usize + (*val > 0xFFFF) as usize + (*val > 0xFFFFFF) as usize;
-                }
-                tme.borrow_mut().stop();
-                ret[0]
-            });
-        });
-    }
-
-    // sec: end
-    stm.extend(quote! {
-        stdy.reg_bld(&[Acm(1), Rd, Ptr], |x| {
-            #stm_inr
-        });
-    });
-
-    stm
-}
-
-pub fn emit_acm_rd_val() -> TokenStream {
-    let mut stm = TokenStream::new();
-
-    // sec: inner
-    let mut stm_inr = TokenStream::new();
-    for len in ACM_RNG.clone().map(|x| 2u32.pow(x)) {
-        let lit_len = Literal::u32_unsuffixed(len);
-        stm_inr.extend(quote! {
-            x.ins_prm(Len(#lit_len), |tme| {
-                let mut vals: Vec<u32> = (0u32..#lit_len).collect();
-                let mut rng = thread_rng();
-                vals.shuffle(&mut rng);
-                let mut ret = [0usize; 1];
-                tme.borrow_mut().start();
-                for val in vals.iter() {
-                    let val = *val;
-                    ret[0] += (val > 0xFF) as usize + (val > 0xFFFF) as usize + (val > 0xFFFFFF) as usize;
-                }
-                tme.borrow_mut().stop();
-                ret[0]
-            });
-        });
-    }
-
-    // sec: end
-    stm.extend(quote! {
-        stdy.reg_bld(&[Acm(1), Rd, Val], |x| {
-            #stm_inr
-        });
-    });
-
-    stm
-}
-
-pub fn emit_acm_add_cnt() -> TokenStream {
-    let mut stm = TokenStream::new();
-
-    // sec: inner
-    let mut stm_inr = TokenStream::new();
-    for len in ACM_RNG.clone().map(|x| 2u32.pow(x)) {
-        let lit_len = Literal::u32_unsuffixed(len);
-        stm_inr.extend(quote! {
-            x.ins_prm(Len(#lit_len), |tme| {
-                let mut vals: Vec<u32> = (0u32..#lit_len).collect();
-                let mut rng = thread_rng();
-                vals.shuffle(&mut rng);
-                let mut ret = [0usize; 1];
-                tme.borrow_mut().start();
-                ret[0] = vals.len();
-                for val in vals.iter() {
-                    let val = *val;
-                    ret[0] += (val > 0xFF) as usize + (val > 0xFFFF) as usize + (val > 0xFFFFFF) as usize;
-                }
-                tme.borrow_mut().stop();
-                ret[0]
-            });
-        });
-    }
-
-    // sec: end
-    stm.extend(quote! {
-        stdy.reg_bld(&[Acm(1), Add, Cnt], |x| {
-            #stm_inr
-        });
-    });
-
-    stm
-}
-
-pub fn emit_acm_add_one() -> TokenStream {
-    let mut stm = TokenStream::new();
-
-    // sec: inner
-    let mut stm_inr = TokenStream::new();
-    for len in ACM_RNG.clone().map(|x| 2u32.pow(x)) {
-        let lit_len = Literal::u32_unsuffixed(len);
-        stm_inr.extend(quote! {
-            x.ins_prm(Len(#lit_len), |tme| {
-                let mut vals: Vec<u32> = (0u32..#lit_len).collect();
-                let mut rng = thread_rng();
-                vals.shuffle(&mut rng);
-                let mut ret = [0usize; 1];
-                tme.borrow_mut().start();
-                for val in vals.iter() {
-                    let val = *val;
-                    ret[0] += 1usize + (val > 0xFF) as usize + (val > 0xFFFF) as usize + (val > 0xFFFFFF) as usize;
-                }
-                tme.borrow_mut().stop();
-                ret[0]
-            });
-        });
-    }
-
-    // sec: end
-    stm.extend(quote! {
-        stdy.reg_bld(&[Acm(1), Add, One], |x| {
-            #stm_inr
-        });
-    });
-
-    stm
-}
-
-pub fn emit_acm1_unr1_thd1() -> TokenStream {
-    let mut stm = TokenStream::new();
-
-    // sec: inner
-    let mut stm_inr = TokenStream::new();
-    for len in ACM_RNG.clone().map(|x| 2u32.pow(x)) {
-        let lit_len = Literal::u32_unsuffixed(len);
-        stm_inr.extend(quote! {
-            x.ins_prm(Len(#lit_len), |tme| {
-                let mut vals: Vec<u32> = (0u32..#lit_len).collect();
-                let mut rng = thread_rng();
-                vals.shuffle(&mut rng);
-                let mut ret = [0u32; 1];
-                let mut n: usize = 0;
-                tme.borrow_mut().start();
-                while n < #lit_len {
-                    ret[0] += vals[n];
-                    n += 1;
-                }
-                tme.borrow_mut().stop();
-                ret[0]
-            });
-        });
-    }
-
-    // sec: end
-    stm.extend(quote! {
-        stdy.reg_bld(&[Acm(1), Unr(1), Thd(1)], |x| {
-            #stm_inr
-        });
-    });
-
-    stm
-}
-
-pub fn emit_acm2_unr2_thd1() -> TokenStream {
-    let mut stm = TokenStream::new();
-
-    // sec: inner
-    let mut stm_inr = TokenStream::new();
-    for len in ACM_RNG.clone().map(|x| 2u32.pow(x)) {
-        let lit_len = Literal::u32_unsuffixed(len);
-        stm_inr.extend(quote! {
-            x.ins_prm(Len(#lit_len), |tme| {
-                let mut vals: Vec<u32> = (0u32..#lit_len).collect();
-                let mut rng = thread_rng();
-                vals.shuffle(&mut rng);
-                let mut ret = [0u32; 2];
-                let mut n: usize = 0;
-                tme.borrow_mut().start();
-                while n < #lit_len {
-                    ret[0] += vals[n];
-                    ret[1] += vals[n + 1];
-                    n += 2;
-                }
-                tme.borrow_mut().stop();
-                ret[0] + ret[1]
-            });
-        });
-    }
-
-    // sec: end
-    stm.extend(quote! {
-        stdy.reg_bld(&[Acm(2), Unr(2), Thd(1)], |x| {
-            #stm_inr
-        });
-    });
-
-    stm
-}
-
-pub fn emit_acm1_unr8_thd1() -> TokenStream {
-    let mut stm = TokenStream::new();
-
-    // sec: inner
-    let mut stm_inr = TokenStream::new();
-    for len in ACM_RNG.clone().map(|x| 2u32.pow(x)) {
-        let lit_len = Literal::u32_unsuffixed(len);
-        stm_inr.extend(quote! {
-            x.ins_prm(Len(#lit_len), |tme| {
-                let mut vals: Vec<u32> = (0u32..#lit_len).collect();
-                let mut rng = thread_rng();
-                vals.shuffle(&mut rng);
-                let mut ret = [0u32; 1];
-                let mut n: usize = 0;
-                tme.borrow_mut().start();
-                while n < #lit_len {
-                    ret[0] += vals[n];
-                    ret[0] += vals[n + 1];
-                    ret[0] += vals[n + 2];
-                    ret[0] += vals[n + 3];
-                    ret[0] += vals[n + 4];
-                    ret[0] += vals[n + 5];
-                    ret[0] += vals[n + 6];
-                    ret[0] += vals[n + 7];
-                    n += 8;
-                }
-                tme.borrow_mut().stop();
-                ret[0]
-            });
-        });
-    }
-
-    // sec: end
-    stm.extend(quote! {
-        stdy.reg_bld(&[Acm(1), Unr(8), Thd(1)], |x| {
-            #stm_inr
-        });
-    });
-
-    stm
-}
-
-pub fn emit_acm8_unr8_thd1() -> TokenStream {
-    let mut stm = TokenStream::new();
-
-    // sec: inner
-    let mut stm_inr = TokenStream::new();
-    for len in ACM_RNG.clone().map(|x| 2u32.pow(x)) {
-        let lit_len = Literal::u32_unsuffixed(len);
-        stm_inr.extend(quote! {
-            x.ins_prm(Len(#lit_len), |tme| {
-                let mut vals: Vec<u32> = (0u32..#lit_len).collect();
-                let mut rng = thread_rng();
-                vals.shuffle(&mut rng);
-                let mut ret = [0u32; 8];
-                let mut n: usize = 0;
-                tme.borrow_mut().start();
-                while n < #lit_len {
-                    ret[0] += vals[n];
-                    ret[1] += vals[n + 1];
-                    ret[2] += vals[n + 2];
-                    ret[3] += vals[n + 3];
-                    ret[4] += vals[n + 4];
-                    ret[5] += vals[n + 5];
-                    ret[6] += vals[n + 6];
-                    ret[7] += vals[n + 7];
-                    n += 8;
-                }
-                let ret_all = ret[0] + ret[1] + ret[2] + ret[3] + ret[4] + ret[5] + ret[6] + ret[7];
-                tme.borrow_mut().stop();
-                ret_all
-            });
-        });
-    }
-
-    // sec: end
-    stm.extend(quote! {
-        stdy.reg_bld(&[Acm(8), Unr(8), Thd(1)], |x| {
-            #stm_inr
-        });
-    });
-
-    stm
-}
-
-pub fn emit_acm16_unr16_thd1() -> TokenStream {
-    let mut stm = TokenStream::new();
-
-    // sec: inner
-    let mut stm_inr = TokenStream::new();
-    for len in ACM_RNG.clone().map(|x| 2u32.pow(x)) {
-        let lit_len = Literal::u32_unsuffixed(len);
-        stm_inr.extend(quote! {
-            x.ins_prm(Len(#lit_len), |tme| {
-                let mut vals: Vec<u32> = (0u32..#lit_len).collect();
-                let mut rng = thread_rng();
-                vals.shuffle(&mut rng);
-                let mut ret = [0u32; 16];
-                let mut n: usize = 0;
-                tme.borrow_mut().start();
-                while n < #lit_len {
-                    ret[0] += vals[n];
-                    ret[1] += vals[n + 1];
-                    ret[2] += vals[n + 2];
-                    ret[3] += vals[n + 3];
-                    ret[4] += vals[n + 4];
-                    ret[5] += vals[n + 5];
-                    ret[6] += vals[n + 6];
-                    ret[7] += vals[n + 7];
-                    ret[8] += vals[n + 8];
-                    ret[9] += vals[n + 9];
-                    ret[10] += vals[n + 10];
-                    ret[11] += vals[n + 11];
-                    ret[12] += vals[n + 12];
-                    ret[13] += vals[n + 13];
-                    ret[14] += vals[n + 14];
-                    ret[15] += vals[n + 15];
-                    n += 16;
-                }
-                let mut ret_all = ret[0] + ret[1] + ret[2] + ret[3] + ret[4] + ret[5] + ret[6] + ret[7]; 
-                ret_all += ret[8] + ret[9] + ret[10] + ret[11] + ret[12] + ret[13] + ret[14] + ret[15];
-                tme.borrow_mut().stop();
-                ret_all
-            });
-        });
-    }
-
-    // sec: end
-    stm.extend(quote! {
-        stdy.reg_bld(&[Acm(16), Unr(16), Thd(1)], |x| {
-            #stm_inr
-        });
-    });
-
-    stm
-}
-
-pub static PLL_RNG: Range<u32> = 4..18;
-
-pub fn emit_acm1_unr1_thd2_join() -> TokenStream {
-    let mut stm = TokenStream::new();
-
-    // sec: inner
-    let mut stm_inr = TokenStream::new();
-    for len in PLL_RNG.clone().map(|x| 2u32.pow(x)) {
-        let lit_len = Literal::u32_unsuffixed(len);
-        stm_inr.extend(quote! {
-            x.ins_prm(Len(#lit_len), |tme| {
-                // Create a list of random u32s.
-                let mut vals: Vec<u32> = (0u32..#lit_len).collect();
-                let mut rnd_rng = thread_rng();
-                vals.shuffle(&mut rnd_rng);
-                let vals: Arc<Vec<u32>> = Arc::new(vals);
-
-                // Sum the list of values in parallel.
-                // Use a separate accumulator in each thread.
-                let thd_cnt: usize = 2;
-                let mut hndls: Vec<JoinHandle<u32>> = Vec::with_capacity(thd_cnt);
-                tme.borrow_mut().start();
-                for rng in rngs(thd_cnt, vals.len()) {
-                    let vals_clone = vals.clone();
-                    let hndl = thread::spawn(move || {
-                        let mut acm: u32 = 0;
-                        let vals_read: &Vec<u32> = vals_clone.borrow();
-                        for idx in rng {
-                            acm += vals_read[idx];
-                        }
-                        acm
-                    });
-                    hndls.push(hndl);
-                }
-
-                // Combine the separate accumulators into a single value.
-                let mut sum: u32 = 0;
-                for hndl in hndls {
-                    sum += hndl.join().unwrap();
-                }
-                tme.borrow_mut().stop();
-                sum
-            });
-        });
-    }
-
-    // sec: end
-    stm.extend(quote! {
-        stdy.reg_bld(&[Acm(1), Unr(1), Thd(2), Join], |x| {
-            #stm_inr
-        });
-    });
-
-    stm
-}
-
-pub fn emit_acm1_unr1_thd2_mpsc() -> TokenStream {
-    let mut stm = TokenStream::new();
-
-    // sec: inner
-    let mut stm_inr = TokenStream::new();
-    for len in PLL_RNG.clone().map(|x| 2u32.pow(x)) {
-        let lit_len = Literal::u32_unsuffixed(len);
-        stm_inr.extend(quote! {
-            x.ins_prm(Len(#lit_len), |tme| {
-                // Create a list of random u32s.
-                let mut vals: Vec<u32> = (0u32..#lit_len).collect();
-                let mut rnd_rng = thread_rng();
-                vals.shuffle(&mut rnd_rng);
-                let vals: Arc<Vec<u32>> = Arc::new(vals);
-
-                // Sum the list of values in parallel.
-                // Use a separate accumulator in each thread.
-                let thd_cnt: usize = 2;
-                let pool = ThreadPool::new(thd_cnt);
-                let (tx, rx) = channel();
-                tme.borrow_mut().start();
-                for rng in rngs(thd_cnt, vals.len()) {
-                    let vals = vals.clone();
-                    let tx = tx.clone();
-                    pool.execute(move || {
-                        let mut acm: u32 = 0;
-                        let vals: &Vec<u32> = vals.borrow();
-                        for idx in rng {
-                            acm += vals[idx];
-                        }
-                        tx.send(acm).unwrap();
-                    });
-                }
-
-                // Combine the separate accumulators into a single value.
-                let sum = rx.iter().take(thd_cnt).sum::<u32>();
-                tme.borrow_mut().stop();
-                sum
-            });
-        });
-    }
-
-    // sec: end
-    stm.extend(quote! {
-        stdy.reg_bld(&[Acm(1), Unr(1), Thd(2), Mpsc], |x| {
-            #stm_inr
-        });
-    });
-
-    stm
-}
-
-pub fn emit_acm1_unr1_thd4_mpsc() -> TokenStream {
-    let mut stm = TokenStream::new();
-
-    // sec: inner
-    let mut stm_inr = TokenStream::new();
-    for len in PLL_RNG.clone().map(|x| 2u32.pow(x)) {
-        let lit_len = Literal::u32_unsuffixed(len);
-        stm_inr.extend(quote! {
-            x.ins_prm(Len(#lit_len), |tme| {
-                // Create a list of random u32s.
-                let mut vals: Vec<u32> = (0u32..#lit_len).collect();
-                let mut rnd_rng = thread_rng();
-                vals.shuffle(&mut rnd_rng);
-                let vals: Arc<Vec<u32>> = Arc::new(vals);
-
-                // Sum the list of values in parallel.
-                // Use a separate accumulator in each thread.
-                let thd_cnt: usize = 4;
-                let pool = ThreadPool::new(thd_cnt);
-                let (tx, rx) = channel();
-                tme.borrow_mut().start();
-                for rng in rngs(thd_cnt, vals.len()) {
-                    let vals = vals.clone();
-                    let tx = tx.clone();
-                    pool.execute(move || {
-                        let mut acm: u32 = 0;
-                        let vals: &Vec<u32> = vals.borrow();
-                        for idx in rng {
-                            acm += vals[idx];
-                        }
-                        tx.send(acm).unwrap();
-                    });
-                }
-
-                // Combine the separate accumulators into a single value.
-                let sum = rx.iter().take(thd_cnt).sum::<u32>();
-                tme.borrow_mut().stop();
-                sum
-            });
-        });
-    }
-
-    // sec: end
-    stm.extend(quote! {
-        stdy.reg_bld(&[Acm(1), Unr(1), Thd(4), Mpsc], |x| {
-            #stm_inr
-        });
-    });
-
-    stm
-}
-
-pub fn emit_acm1_unr1_thd8_mpsc() -> TokenStream {
-    let mut stm = TokenStream::new();
-
-    // sec: inner
-    let mut stm_inr = TokenStream::new();
-    for len in PLL_RNG.clone().map(|x| 2u32.pow(x)) {
-        let lit_len = Literal::u32_unsuffixed(len);
-        stm_inr.extend(quote! {
-            x.ins_prm(Len(#lit_len), |tme| {
-                // Create a list of random u32s.
-                let mut vals: Vec<u32> = (0u32..#lit_len).collect();
-                let mut rnd_rng = thread_rng();
-                vals.shuffle(&mut rnd_rng);
-                let vals: Arc<Vec<u32>> = Arc::new(vals);
-
-                // Sum the list of values in parallel.
-                // Use a separate accumulator in each thread.
-                let thd_cnt: usize = 8;
-                let pool = ThreadPool::new(thd_cnt);
-                let (tx, rx) = channel();
-                tme.borrow_mut().start();
-                for rng in rngs(thd_cnt, vals.len()) {
-                    let vals = vals.clone();
-                    let tx = tx.clone();
-                    pool.execute(move || {
-                        let mut acm: u32 = 0;
-                        let vals: &Vec<u32> = vals.borrow();
-                        for idx in rng {
-                            acm += vals[idx];
-                        }
-                        tx.send(acm).unwrap();
-                    });
-                }
-
-                // Combine the separate accumulators into a single value.
-                let sum = rx.iter().take(thd_cnt).sum::<u32>();
-                tme.borrow_mut().stop();
-                sum
-            });
-        });
     }
-
-    // sec: end
-    stm.extend(quote! {
-        stdy.reg_bld(&[Acm(1), Unr(1), Thd(8), Mpsc], |x| {
-            #stm_inr
-        });
-    });
-
-    stm
 }
-
-pub fn emit_acm1_unr1_thd16_mpsc() -> TokenStream {
-    let mut stm = TokenStream::new();
-
-    // sec: inner
-    let mut stm_inr = TokenStream::new();
-    for len in PLL_RNG.clone().map(|x| 2u32.pow(x)) {
-        let lit_len = Literal::u32_unsuffixed(len);
-        stm_inr.extend(quote! {
-            x.ins_prm(Len(#lit_len), |tme| {
-                // Create a list of random u32s.
-                let mut vals: Vec<u32> = (0u32..#lit_len).collect();
-                let mut rnd_rng = thread_rng();
-                vals.shuffle(&mut rnd_rng);
-                let vals: Arc<Vec<u32>> = Arc::new(vals);
-
-                // Sum the list of values in parallel.
-                // Use a separate accumulator in each thread.
-                let thd_cnt: usize = 16;
-                let pool = ThreadPool::new(thd_cnt);
-                let (tx, rx) = channel();
-                tme.borrow_mut().start();
-                for rng in rngs(thd_cnt, vals.len()) {
-                    let vals = vals.clone();
-                    let tx = tx.clone();
-                    pool.execute(move || {
-                        let mut acm: u32 = 0;
-                        let vals: &Vec<u32> = vals.borrow();
-                        for idx in rng {
-                            acm += vals[idx];
-                        }
-                        tx.send(acm).unwrap();
-                    });
-                }
-
-                // Combine the separate accumulators into a single value.
-                let sum = rx.iter().take(thd_cnt).sum::<u32>();
-                tme.borrow_mut().stop();
-                sum
-            });
-        });
-    }
-
-    // sec: end
-    stm.extend(quote! {
-        stdy.reg_bld(&[Acm(1), Unr(1), Thd(16), Mpsc], |x| {
-            #stm_inr
-        });
-    });
-
-    stm
-}
\ No newline at end of file
@@ -0,0 +1,53 @@
+use crate::*;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Default target relative width of the confidence interval of the median, in percent.
+pub const WDT_DFT: f64 = 2.0;
+
+/// Default time budget of each series in seconds.
+pub const BGT_DFT: f64 = 10.0;
+
+/// Maximum iterations per benchmark function in adaptive mode.
+pub const ITR_MAX: usize = 1 << 16;
+
+/// Stopping rule of adaptive sampling.
+///
+/// Each benchmark function is sampled until the confidence interval of its median is
+/// within a relative width, or the time budget of its series runs out.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Adp {
+    /// Target relative width of the confidence interval of the median, in percent.
+    pub wdt: f64,
+    /// Time budget of each series in seconds.
+    pub bgt: f64,
+}
+
+impl Default for Adp {
+    fn default() -> Self {
+        Self {
+            wdt: WDT_DFT,
+            bgt: BGT_DFT,
+        }
+    }
+}
+
+impl Adp {
+    /// Returns the time budget of each series, saturating at the longest duration.
+    pub fn bgt(&self) -> Duration {
+        Duration::try_from_secs_f64(self.bgt).unwrap_or(Duration::MAX)
+    }
+
+    /// Returns true when the confidence interval of the median of samples is within the
+    /// target width.
+    pub fn cnv(&self, smps: &[f64]) -> bool {
+        ci_wdt(smps) <= self.wdt
+    }
+}
+
+/// Returns the width of the bootstrap confidence interval of the median of samples, in
+/// percent of the median.
+pub fn ci_wdt(smps: &[f64]) -> f64 {
+    let (lo, hi) = mdn_ci(smps);
+    (hi - lo) / mdn(smps).max(CYC_MIN) * 100.0
+}
@@ -0,0 +1,207 @@
+use anyhow::{bail, Context, Result};
+use std::sync::OnceLock;
+
+/// Pinning set up for the run, when any.
+static PIN: OnceLock<Pin> = OnceLock::new();
+
+/// CPU pinning of the measuring thread and workers.
+#[derive(Debug, Clone)]
+struct Pin {
+    /// CPU of the measuring thread, when pinned.
+    cpu: Option<usize>,
+    /// CPUs the process could run on before pinning.
+    org: Vec<usize>,
+    /// CPUs of workers, assigned round-robin by worker index.
+    wkrs: Vec<usize>,
+}
+
+/// Pins the calling thread, which measures cycles, to a CPU and sets the CPUs of workers.
+///
+/// Threads inherit the affinity of the thread spawning them, so workers which call
+/// `pin_wkr` without worker CPUs are returned to the CPUs the process started with.
+pub fn pin(cpu: Option<usize>, wkrs: &[usize]) -> Result<()> {
+    if cpu.is_none() && wkrs.is_empty() {
+        return Ok(());
+    }
+    let org = afn();
+    for cpu in cpu.iter().chain(wkrs.iter()) {
+        if !org.contains(cpu) {
+            bail!(
+                "CPU {} isn't available, expected one of {}",
+                cpu,
+                fmt_cpus(&org)
+            );
+        }
+    }
+    if let Some(cpu) = cpu {
+        set_afn(&[cpu])?;
+    }
+    let pin = Pin {
+        cpu,
+        org,
+        wkrs: wkrs.to_vec(),
+    };
+    if PIN.set(pin).is_err() {
+        bail!("CPU pinning is already set");
+    }
+    Ok(())
+}
+
+/// Pins the calling worker thread by worker index.
+///
+/// Persistent workers call this once, outside any timed region. Does nothing when the run
+/// isn't pinned.
+pub fn pin_wkr(idx: usize) {
+    let Some(pin) = PIN.get() else {
+        return;
+    };
+    let ret = match pin.wkrs.is_empty() {
+        true => set_afn(&pin.org),
+        false => set_afn(&[pin.wkrs[idx % pin.wkrs.len()]]),
+    };
+    ret.expect("failed to pin a worker");
+}
+
+/// Returns the count of CPUs the process may run on, before any pinning.
+pub fn ncpu() -> usize {
+    match PIN.get() {
+        Some(pin) => pin.org.len(),
+        None => std::thread::available_parallelism().map_or(1, |cnt| cnt.get()),
+    }
+}
+
+/// Returns a multiple of the CPU count as a thread count of generated studies.
+///
+/// Counts below two, or among the fixed counts of the same studies, are skipped.
+pub fn ncpu_thd(mlt: usize, fxd: &[usize]) -> Option<usize> {
+    let thd = ncpu() * mlt;
+    (thd >= 2 && !fxd.contains(&thd)).then_some(thd)
+}
+
+/// Returns the CPU the measuring thread is pinned to, when any.
+pub fn msr_cpu() -> Option<usize> {
+    PIN.get().and_then(|pin| pin.cpu)
+}
+
+/// Returns the CPUs of workers, empty when workers aren't pinned.
+pub fn wkr_cpus() -> Vec<usize> {
+    PIN.get().map(|pin| pin.wkrs.clone()).unwrap_or_default()
+}
+
+/// Parses a CPU list, such as `0,2-5`.
+pub fn prs_cpus(expr: &str) -> Result<Vec<usize>> {
+    let mut ret: Vec<usize> = Vec::new();
+    for tok in expr
+        .split(',')
+        .map(|tok| tok.trim())
+        .filter(|tok| !tok.is_empty())
+    {
+        let cpu = |val: &str| -> Result<usize> {
+            val.trim()
+                .parse()
+                .ok()
+                .with_context(|| format!("invalid CPU '{}' in '{}'", val, expr))
+        };
+        match tok.split_once('-') {
+            Some((fst, lst)) => {
+                let (fst, lst) = (cpu(fst)?, cpu(lst)?);
+                if fst > lst {
+                    bail!("CPU range '{}' is descending", tok);
+                }
+                ret.extend(fst..=lst);
+            }
+            None => ret.push(cpu(tok)?),
+        }
+    }
+    if ret.is_empty() {
+        bail!("empty CPU list '{}'", expr);
+    }
+    Ok(ret)
+}
+
+/// Returns a CPU list formatted with ranges, such as `0,2-5`.
+pub fn fmt_cpus(cpus: &[usize]) -> String {
+    let mut rngs: Vec<(usize, usize)> = Vec::new();
+    for cpu in cpus.iter() {
+        match rngs.last_mut() {
+            Some((_, lst)) if *lst + 1 == *cpu => *lst = *cpu,
+            _ => rngs.push((*cpu, *cpu)),
+        }
+    }
+    rngs.iter()
+        .map(|(fst, lst)| match fst == lst {
+            true => fst.to_string(),
+            false => format!("{}-{}", fst, lst),
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Returns the CPUs the calling thread may run on.
+#[cfg(target_os = "linux")]
+pub fn afn() -> Vec<usize> {
+    // SAFETY: `cpu_set_t` is plain data and the size passed matches the set.
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        if libc::sched_getaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &mut set) != 0 {
+            return Vec::new();
+        }
+        (0..libc::CPU_SETSIZE as usize)
+            .filter(|cpu| libc::CPU_ISSET(*cpu, &set))
+            .collect()
+    }
+}
+
+/// Returns the CPUs the calling thread may run on.
+#[cfg(not(target_os = "linux"))]
+pub fn afn() -> Vec<usize> {
+    Vec::new()
+}
+
+/// Sets the CPUs the calling thread may run on.
+#[cfg(target_os = "linux")]
+pub fn set_afn(cpus: &[usize]) -> Result<()> {
+    if let Some(cpu) = cpus.iter().find(|cpu| **cpu >= libc::CPU_SETSIZE as usize) {
+        bail!("CPU {} is beyond the affinity set size", cpu);
+    }
+    // SAFETY: `cpu_set_t` is plain data, CPUs are within its size and the size passed matches.
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        for cpu in cpus.iter() {
+            libc::CPU_SET(*cpu, &mut set);
+        }
+        if libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) != 0 {
+            bail!(
+                "failed to pin to CPUs {}: {}",
+                fmt_cpus(cpus),
+                std::io::Error::last_os_error()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Sets the CPUs the calling thread may run on.
+#[cfg(not(target_os = "linux"))]
+pub fn set_afn(_cpus: &[usize]) -> Result<()> {
+    bail!("CPU pinning needs Linux sched_setaffinity")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cpus() {
+        assert_eq!(prs_cpus("2-5,7").unwrap(), vec![2, 3, 4, 5, 7]);
+        assert_eq!(prs_cpus("0, 2 ,4").unwrap(), vec![0, 2, 4]);
+        assert_eq!(fmt_cpus(&prs_cpus("0,2-5").unwrap()), "0,2-5");
+    }
+
+    #[test]
+    fn cpus_err() {
+        for expr in ["", ",", "5-2", "a", "1-", "-1"] {
+            assert!(prs_cpus(expr).is_err(), "'{}' parsed", expr);
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use crate::*;
+use std::cell::RefCell;
+
+/// Raw cycles of a timed window below which a benchmark function is batched.
+///
+/// Shorter windows are dominated by the cost and granularity of reading the counter.
+pub const BAT_WND: f64 = 1000.0;
+
+/// Maximum batch factor of `ins` functions.
+pub const BAT_MAX: u32 = 1024;
+
+/// Probe samples used to choose a batch factor.
+pub const BAT_PRB: u16 = 8;
+
+/// Returns the batch factor of a benchmark function.
+///
+/// Functions whose probed window is at least `BAT_WND` run once per sample. Shorter `ins`
+/// functions repeat enough times for their corrected cycles to fill the window. `ins_prm`
+/// functions always run once, since their setup can't repeat outside the timed window and
+/// separate windows would each pay the whole overhead.
+pub fn bat_fct(knd: Knd, ovh: Ovh, tme: &RefCell<Tme>, f: &Fun) -> u32 {
+    if knd == Knd::InsPrm {
+        return 1;
+    }
+    let smps: Vec<f64> = (0..BAT_PRB).map(|_| bat_smp(1, tme, f).0).collect();
+    let raw = mdn(&smps);
+    if raw >= BAT_WND {
+        return 1;
+    }
+    let cyc = (raw - ovh.mdn).max(1.0);
+    (BAT_WND / cyc).clamp(1.0, BAT_MAX as f64) as u32
+}
+
+/// Returns one sample of cycles and hardware counts per invocation of a batch.
+///
+/// A batch runs inside one timed window. Cycles keep the fraction left by the batch, which
+/// resolves functions shorter than a cycle.
+pub fn bat_smp(bat: u32, tme: &RefCell<Tme>, f: &Fun) -> (f64, Option<Ctrs>) {
+    f(tme, bat);
+    let tme = tme.borrow();
+    (
+        tme.elp() as f64 / bat as f64,
+        tme.ctrs().map(|ctr| ctr.div(bat)),
+    )
+}
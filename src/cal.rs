@@ -0,0 +1,104 @@
+use crate::*;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+/// Iterations of each empty calibration body.
+pub const CAL_ITR: u16 = 1024;
+
+/// Timer overhead of a way of inserting benchmark functions.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Ovh {
+    /// Median cycles of an empty body, subtracted from samples. Fractional once spread
+    /// across a batch.
+    pub mdn: f64,
+    /// Noise floor, the spread between the 5th and 95th percentile cycles of an empty body.
+    ///
+    /// Corrected medians below it are unreliable.
+    pub nse: f64,
+}
+
+/// Calibrated timer overhead of each way of inserting benchmark functions.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct Cal {
+    /// Overhead of `Ser::ins`: reading the counter, the `RefCell` borrows and the call.
+    pub ins: Ovh,
+    /// Overhead of `Ser::ins_prm`: reading the counter and the `RefCell` borrows.
+    pub ins_prm: Ovh,
+}
+
+impl Cal {
+    /// Times empty bodies with the same wrappers as `Ser::ins` and `Ser::ins_prm`.
+    pub fn run() -> Self {
+        let ins = ovh(&ins_fun(Rc::new(|| ())));
+        let ins_prm = ovh(&ins_prm_fun(Rc::new(|tme: &RefCell<Tme>| {
+            tme.borrow_mut().start();
+            tme.borrow_mut().stop();
+        })));
+        Self { ins, ins_prm }
+    }
+
+    /// Returns the overhead of a kind of benchmark function.
+    pub fn ovh(&self, knd: Knd) -> Ovh {
+        match knd {
+            Knd::Ins => self.ins,
+            Knd::InsPrm => self.ins_prm,
+        }
+    }
+}
+
+impl fmt::Display for Cal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "timer overhead: ins {} cycles (noise {}), ins_prm {} cycles (noise {})",
+            self.ins.mdn, self.ins.nse, self.ins_prm.mdn, self.ins_prm.nse
+        )
+    }
+}
+
+impl Ovh {
+    /// Returns samples less the overhead.
+    pub fn cor(&self, smps: &[f64]) -> Vec<f64> {
+        smps.iter().map(|x| (x - self.mdn).max(0.0)).collect()
+    }
+
+    /// Returns the overhead per invocation of a batched kind of benchmark function.
+    ///
+    /// A batch of `ins` functions shares one timed window, so its overhead is spread across
+    /// the batch. `ins_prm` functions aren't batched and keep the whole overhead.
+    pub fn bat(&self, knd: Knd, bat: u32) -> Ovh {
+        match knd {
+            Knd::Ins => Ovh {
+                mdn: self.mdn / bat as f64,
+                nse: self.nse / bat as f64,
+            },
+            Knd::InsPrm => *self,
+        }
+    }
+
+    /// Returns true when a corrected median is below the noise floor.
+    pub fn is_unr(&self, mdn_cor: f64) -> bool {
+        mdn_cor < self.nse
+    }
+}
+
+/// Returns the overhead of an empty timed function.
+fn ovh(f: &Fun) -> Ovh {
+    let tme = RefCell::new(Tme::new());
+    // Warm up caches and branch predictors.
+    f(&tme, 1);
+    let mut smps: Vec<f64> = (0..CAL_ITR)
+        .map(|_| {
+            f(&tme, 1);
+            let elp = tme.borrow().elp();
+            elp as f64
+        })
+        .collect();
+    smps.sort_unstable_by(f64::total_cmp);
+    Ovh {
+        mdn: mdn(&smps),
+        nse: (pct(&smps, 0.95) - pct(&smps, 0.05)).max(1.0),
+    }
+}
@@ -0,0 +1,267 @@
+use crate::*;
+use anyhow::{bail, Context, Result};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Default number of iterations per benchmark function.
+pub const ITR_DFT: u16 = 64;
+
+/// Command to run.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cmd {
+    /// Runs benchmark studies.
+    Run,
+    /// Compares a baseline run with a current run from saved JSON results.
+    Compare(PathBuf, PathBuf),
+}
+
+/// Command-line options.
+#[derive(Debug, Clone)]
+pub struct Cli {
+    /// Command to run.
+    pub cmd: Cmd,
+    /// Label expressions to select, such as `acm,thd(4)`.
+    pub sels: Vec<String>,
+    /// Pairs of label expressions to compare.
+    pub cmps: Vec<(String, String)>,
+    /// Groups of two or more label expressions to rank.
+    pub rnks: Vec<Vec<String>>,
+    /// Label expressions reporting their fastest label set per length.
+    pub bsts: Vec<String>,
+    /// Ranges of lengths replacing the manifest lengths of runtime length studies.
+    pub lens: Vec<LenRng>,
+    /// Iterations per benchmark function.
+    pub itr: u16,
+    /// Target relative width of the confidence interval of the median, in percent.
+    pub wdt: Option<f64>,
+    /// Time budget of each series in seconds.
+    pub bgt: Option<f64>,
+    /// Directory of saved results.
+    pub out: PathBuf,
+    /// Seed of runtime random generators.
+    pub seed: u64,
+    /// CPU to pin the measuring thread to.
+    pub cpu: Option<usize>,
+    /// CPUs to pin workers of parallel studies to.
+    pub wkr_cpus: Vec<usize>,
+    /// Counts hardware events with the cycles.
+    pub ctr: bool,
+    /// Units of times shown in tables.
+    pub unt: Unt,
+    /// Percentage change beyond which a significant regression fails a comparison.
+    pub thr: f64,
+    /// Lists registered label sets instead of running.
+    pub lst: bool,
+    /// Prints usage instead of running.
+    pub hlp: bool,
+}
+
+impl Default for Cli {
+    fn default() -> Self {
+        Self {
+            cmd: Cmd::Run,
+            sels: Vec::new(),
+            cmps: Vec::new(),
+            rnks: Vec::new(),
+            bsts: Vec::new(),
+            lens: Vec::new(),
+            itr: ITR_DFT,
+            wdt: None,
+            bgt: None,
+            out: PathBuf::from(OUT_DFT),
+            seed: SEED_DFT,
+            cpu: None,
+            wkr_cpus: Vec::new(),
+            ctr: false,
+            unt: Unt::Cyc,
+            thr: THR_DFT,
+            lst: false,
+            hlp: false,
+        }
+    }
+}
+
+/// Usage text for the command-line.
+pub const USAGE: &str = "\
+Measures code performance in CPU cycles.
+
+Usage: mtr [OPTIONS]
+       mtr compare [--thr <PCT>] <BASELINE> <CURRENT>
+
+Commands:
+  compare              Compare two saved JSON results and fail on significant regressions
+
+Options:
+  -s, --sel <EXPR>     Select label sets containing every label in EXPR, e.g. acm,thd(4)
+  -c, --cmp <A:B>      Compare two label expressions, e.g. alc,arr:alc,vct,mcr
+  -r, --rnk <A:B:...>  Rank two or more label expressions per length, e.g. thd(1):thd(4):thd(8)
+  -b, --bst <EXPR>     Report the fastest label set matching EXPR per length, e.g. acm,unr,thd
+      --len <LENS>     Lengths of runtime length studies, comma separated ranges of
+                       FIRST..LAST:xFACTOR or FIRST..LAST:+STEP, e.g. 1k..64M:x2
+                       [default: their ranges in benches.toml]
+  -i, --itr <N>        Iterations per length, or the minimum with --ci or --bgt [default: 64]
+      --ci <PCT>       Sample until the median's confidence interval is within PCT percent
+                       of the median [default with --bgt: 2]
+      --bgt <SECS>     Time budget of each series when sampling adaptively
+                       [default with --ci: 10]
+  -o, --out <DIR>      Directory of saved JSON and CSV results [default: res]
+      --seed <N>       Seed of shuffled inputs [default: 0]
+      --cpu <CPU>      Pin the measuring thread to a CPU
+      --wkr-cpus <CPUS>
+                       Pin persistent workers of pol and mpsc studies to CPUs, e.g. 2-5 or 2,4,6
+      --ctr            Count instructions, branch and cache misses with perf events
+  -u, --unt <UNIT>     Units of tables: cycles, ns or both [default: cycles]
+  -t, --thr <PCT>      Regression threshold of compare in percent [default: 5]
+  -l, --lst            List registered label sets
+  -h, --help           Print help
+
+Without --sel, --cmp, --rnk or --bst, the default comparisons are run.";
+
+impl Cli {
+    /// Parses command-line arguments, excluding the program name.
+    pub fn parse<I>(args: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut ret = Self::default();
+        let mut pos: Vec<String> = Vec::new();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            // Support both `--opt val` and `--opt=val`.
+            let (opt, inl) = match arg.split_once('=') {
+                Some((opt, inl)) if opt.starts_with("--") => {
+                    (opt.to_string(), Some(inl.to_string()))
+                }
+                _ => (arg, None),
+            };
+            let mut val = |opt: &str| -> Result<String> {
+                match inl.clone() {
+                    Some(val) => Ok(val),
+                    None => args
+                        .next()
+                        .with_context(|| format!("missing value for '{}'", opt)),
+                }
+            };
+            match opt.as_str() {
+                "-s" | "--sel" => ret.sels.push(val(&opt)?),
+                "-c" | "--cmp" => {
+                    let cmp = val(&opt)?;
+                    match cmp.split_once(':') {
+                        Some((a, b)) => ret.cmps.push((a.to_string(), b.to_string())),
+                        None => bail!("comparison '{}' isn't in the form A:B", cmp),
+                    }
+                }
+                "-r" | "--rnk" => {
+                    let rnk = val(&opt)?;
+                    let exprs: Vec<String> = rnk.split(':').map(|x| x.to_string()).collect();
+                    if exprs.len() < 2 {
+                        bail!("ranking '{}' isn't in the form A:B:...", rnk);
+                    }
+                    ret.rnks.push(exprs);
+                }
+                "-b" | "--bst" => ret.bsts.push(val(&opt)?),
+                "--len" => {
+                    for rng in val(&opt)?.split(',') {
+                        ret.lens.push(rng.parse()?);
+                    }
+                }
+                "-i" | "--itr" => ret.itr = prs(&opt, &val(&opt)?)?,
+                "--ci" => ret.wdt = Some(prs_pos(&opt, &val(&opt)?)?),
+                "--bgt" => ret.bgt = Some(prs_secs(&opt, &val(&opt)?)?),
+                "-o" | "--out" => ret.out = PathBuf::from(val(&opt)?),
+                "--seed" => ret.seed = prs(&opt, &val(&opt)?)?,
+                "--cpu" => ret.cpu = Some(prs(&opt, &val(&opt)?)?),
+                "--wkr-cpus" => ret.wkr_cpus = prs_cpus(&val(&opt)?)?,
+                "--ctr" => ret.ctr = true,
+                "-u" | "--unt" => ret.unt = val(&opt)?.parse()?,
+                "-t" | "--thr" => ret.thr = prs(&opt, &val(&opt)?)?,
+                "-l" | "--lst" => ret.lst = true,
+                "-h" | "--help" => ret.hlp = true,
+                _ if !opt.starts_with('-') => pos.push(opt),
+                _ => bail!("unknown argument '{}'\n\n{}", opt, USAGE),
+            }
+        }
+
+        match pos.first().map(|x| x.as_str()) {
+            None => {}
+            Some("compare") => match &pos[1..] {
+                [bas, cur] => ret.cmd = Cmd::Compare(PathBuf::from(bas), PathBuf::from(cur)),
+                _ => bail!(
+                    "compare needs a baseline and a current results file\n\n{}",
+                    USAGE
+                ),
+            },
+            Some(cmd) => bail!("unknown command '{}'\n\n{}", cmd, USAGE),
+        }
+
+        Ok(ret)
+    }
+
+    /// Returns true when no selections, comparisons, rankings or best reports were given.
+    pub fn is_dft(&self) -> bool {
+        self.sels.is_empty() && self.cmps.is_empty() && self.rnks.is_empty() && self.bsts.is_empty()
+    }
+
+    /// Returns the stopping rule of adaptive sampling, when `--ci` or `--bgt` was given.
+    pub fn adp(&self) -> Option<Adp> {
+        if self.wdt.is_none() && self.bgt.is_none() {
+            return None;
+        }
+        Some(Adp {
+            wdt: self.wdt.unwrap_or(WDT_DFT),
+            bgt: self.bgt.unwrap_or(BGT_DFT),
+        })
+    }
+
+    /// Returns a query of the command-line selections, comparisons, rankings and best
+    /// reports.
+    pub fn qry(&self) -> Result<QryBld> {
+        let mut qry = QryBld::new();
+        for sel in self.sels.iter() {
+            qry.sel_str(sel)?;
+        }
+        for (a, b) in self.cmps.iter() {
+            let sel_a = qry.sel_str(a)?;
+            let sel_b = qry.sel_str(b)?;
+            qry.cmp(sel_a, sel_b);
+        }
+        for exprs in self.rnks.iter() {
+            let sels = exprs
+                .iter()
+                .map(|expr| qry.sel_str(expr))
+                .collect::<Result<Vec<usize>>>()?;
+            qry.rnk(&sels);
+        }
+        for expr in self.bsts.iter() {
+            let sel = qry.sel_str(expr)?;
+            qry.bst(sel);
+        }
+        Ok(qry)
+    }
+}
+
+/// Parses an option value.
+fn prs<T: FromStr>(opt: &str, val: &str) -> Result<T> {
+    val.parse()
+        .ok()
+        .with_context(|| format!("invalid value '{}' for '{}'", val, opt))
+}
+
+/// Parses a positive, finite option value.
+fn prs_pos(opt: &str, val: &str) -> Result<f64> {
+    let ret: f64 = prs(opt, val)?;
+    if !(ret.is_finite() && ret > 0.0) {
+        bail!("'{}' must be positive, got '{}'", opt, val);
+    }
+    Ok(ret)
+}
+
+/// Parses a positive number of seconds which fits a `Duration`.
+fn prs_secs(opt: &str, val: &str) -> Result<f64> {
+    let ret = prs_pos(opt, val)?;
+    if Duration::try_from_secs_f64(ret).is_err() {
+        bail!("'{}' is too long, got '{}' seconds", opt, val);
+    }
+    Ok(ret)
+}
@@ -0,0 +1,348 @@
+use crate::*;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether timers open hardware counters.
+static CTR_ON: AtomicBool = AtomicBool::new(false);
+
+/// A hardware event counted alongside cycles.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Evt {
+    /// Retired instructions.
+    Ins,
+    /// Core cycles, which differ from time-stamp cycles under frequency scaling.
+    Cyc,
+    /// Mispredicted branches.
+    BrnMis,
+    /// L1 data cache read misses.
+    L1dMis,
+    /// Last level cache misses.
+    LlcMis,
+}
+
+/// Events in the order they're opened. The first available event leads the group.
+pub const EVTS: [Evt; 5] = [Evt::Ins, Evt::Cyc, Evt::BrnMis, Evt::L1dMis, Evt::LlcMis];
+
+/// Hardware counts of one sample. Events which couldn't be opened are `None`.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Ctrs {
+    /// Retired instructions.
+    pub ins: Option<u64>,
+    /// Core cycles.
+    pub cyc: Option<u64>,
+    /// Mispredicted branches.
+    pub brn_mis: Option<u64>,
+    /// L1 data cache read misses.
+    pub l1d_mis: Option<u64>,
+    /// Last level cache misses.
+    pub llc_mis: Option<u64>,
+}
+
+impl Ctrs {
+    /// Returns the count of an event.
+    pub fn get(&self, evt: Evt) -> Option<u64> {
+        match evt {
+            Evt::Ins => self.ins,
+            Evt::Cyc => self.cyc,
+            Evt::BrnMis => self.brn_mis,
+            Evt::L1dMis => self.l1d_mis,
+            Evt::LlcMis => self.llc_mis,
+        }
+    }
+
+    /// Sets the count of an event.
+    pub fn set(&mut self, evt: Evt, val: u64) {
+        match evt {
+            Evt::Ins => self.ins = Some(val),
+            Evt::Cyc => self.cyc = Some(val),
+            Evt::BrnMis => self.brn_mis = Some(val),
+            Evt::L1dMis => self.l1d_mis = Some(val),
+            Evt::LlcMis => self.llc_mis = Some(val),
+        }
+    }
+
+    /// Adds the counts of another sample.
+    pub fn add(&mut self, oth: &Ctrs) {
+        for evt in EVTS {
+            if let Some(val) = oth.get(evt) {
+                self.set(evt, self.get(evt).unwrap_or_default() + val);
+            }
+        }
+    }
+
+    /// Returns the counts divided by a batch factor.
+    pub fn div(&self, bat: u32) -> Ctrs {
+        let mut ret = Ctrs::default();
+        for evt in EVTS {
+            if let Some(val) = self.get(evt) {
+                ret.set(evt, val / bat as u64);
+            }
+        }
+        ret
+    }
+
+    /// Returns instructions per core cycle.
+    pub fn ipc(&self) -> Option<f64> {
+        match (self.ins, self.cyc) {
+            (Some(ins), Some(cyc)) if cyc > 0 => Some(ins as f64 / cyc as f64),
+            _ => None,
+        }
+    }
+
+    /// Returns the median count of each event across samples.
+    pub fn mdn(smps: &[Ctrs]) -> Option<Ctrs> {
+        if smps.is_empty() {
+            return None;
+        }
+        let mut ret = Ctrs::default();
+        for evt in EVTS {
+            let vals: Vec<f64> = smps
+                .iter()
+                .filter_map(|smp| smp.get(evt))
+                .map(|x| x as f64)
+                .collect();
+            if !vals.is_empty() {
+                ret.set(evt, mdn(&vals).round() as u64);
+            }
+        }
+        Some(ret)
+    }
+}
+
+impl Evt {
+    /// Returns the name of the event shown in tables.
+    pub fn nam(&self) -> &'static str {
+        match self {
+            Evt::Ins => "instructions",
+            Evt::Cyc => "core cycles",
+            Evt::BrnMis => "branch misses",
+            Evt::L1dMis => "L1D misses",
+            Evt::LlcMis => "LLC misses",
+        }
+    }
+}
+
+/// Enables hardware counters in timers created by `Tme::new`.
+///
+/// Probes the counters first. When they can't be opened, such as when
+/// `/proc/sys/kernel/perf_event_paranoid` forbids it, prints a warning and runs on with
+/// cycles only. Returns whether counters are enabled.
+pub fn set_ctr(on: bool) -> bool {
+    let on = on
+        && match Ctr::open() {
+            Ok(ctr) => {
+                let evts: Vec<&str> = ctr.evts.iter().map(|evt| evt.nam()).collect();
+                println!("counting {}", evts.join(", "));
+                true
+            }
+            Err(err) => {
+                eprintln!(
+                    "warning: hardware counters are unavailable, counting cycles only: {}",
+                    err
+                );
+                false
+            }
+        };
+    CTR_ON.store(on, Ordering::Relaxed);
+    on
+}
+
+/// Returns whether hardware counters are enabled.
+pub fn ctr_on() -> bool {
+    CTR_ON.load(Ordering::Relaxed)
+}
+
+/// A group of hardware counters of the calling thread, opened with `perf_event_open`.
+#[derive(Debug)]
+pub struct Ctr {
+    /// File descriptors, the first leads the group.
+    fds: Vec<i32>,
+    /// Events of each file descriptor.
+    pub evts: Vec<Evt>,
+}
+
+#[cfg(target_os = "linux")]
+mod sys {
+    use super::*;
+    use anyhow::bail;
+
+    const PERF_TYPE_HARDWARE: u32 = 0;
+    const PERF_TYPE_HW_CACHE: u32 = 3;
+    const PERF_COUNT_HW_CPU_CYCLES: u64 = 0;
+    const PERF_COUNT_HW_INSTRUCTIONS: u64 = 1;
+    const PERF_COUNT_HW_CACHE_MISSES: u64 = 3;
+    const PERF_COUNT_HW_BRANCH_MISSES: u64 = 5;
+    // L1D cache (0), read operation (0 << 8), miss result (1 << 16).
+    const PERF_COUNT_HW_CACHE_L1D_READ_MISS: u64 = 1 << 16;
+    const PERF_FORMAT_GROUP: u64 = 1 << 3;
+    const PERF_FLAG_FD_CLOEXEC: libc::c_ulong = 1 << 3;
+    const PERF_EVENT_IOC_ENABLE: libc::c_ulong = 0x2400;
+    const PERF_EVENT_IOC_DISABLE: libc::c_ulong = 0x2401;
+    const PERF_EVENT_IOC_RESET: libc::c_ulong = 0x2403;
+    const PERF_IOC_FLAG_GROUP: libc::c_ulong = 1;
+    // Flag bits of `perf_event_attr`.
+    const ATTR_DISABLED: u64 = 1 << 0;
+    const ATTR_EXCLUDE_KERNEL: u64 = 1 << 5;
+    const ATTR_EXCLUDE_HV: u64 = 1 << 6;
+
+    /// First version of `perf_event_attr`, which every kernel with perf events accepts.
+    #[repr(C)]
+    #[derive(Default)]
+    struct PerfEventAttr {
+        typ: u32,
+        size: u32,
+        config: u64,
+        sample_period: u64,
+        sample_type: u64,
+        read_format: u64,
+        flags: u64,
+        wakeup_events: u32,
+        bp_type: u32,
+        config1: u64,
+    }
+
+    fn evt_attr(evt: Evt) -> PerfEventAttr {
+        let (typ, config) = match evt {
+            Evt::Ins => (PERF_TYPE_HARDWARE, PERF_COUNT_HW_INSTRUCTIONS),
+            Evt::Cyc => (PERF_TYPE_HARDWARE, PERF_COUNT_HW_CPU_CYCLES),
+            Evt::BrnMis => (PERF_TYPE_HARDWARE, PERF_COUNT_HW_BRANCH_MISSES),
+            Evt::L1dMis => (PERF_TYPE_HW_CACHE, PERF_COUNT_HW_CACHE_L1D_READ_MISS),
+            Evt::LlcMis => (PERF_TYPE_HARDWARE, PERF_COUNT_HW_CACHE_MISSES),
+        };
+        PerfEventAttr {
+            typ,
+            size: std::mem::size_of::<PerfEventAttr>() as u32,
+            config,
+            read_format: PERF_FORMAT_GROUP,
+            // Count user space only, which unprivileged processes may do.
+            flags: ATTR_DISABLED | ATTR_EXCLUDE_KERNEL | ATTR_EXCLUDE_HV,
+            ..Default::default()
+        }
+    }
+
+    fn evt_open(evt: Evt, grp: i32) -> std::io::Result<i32> {
+        let attr = evt_attr(evt);
+        // SAFETY: the attribute outlives the call and its size field matches its layout.
+        let fd = unsafe {
+            libc::syscall(
+                libc::SYS_perf_event_open,
+                &attr as *const PerfEventAttr,
+                0 as libc::pid_t,
+                -1 as libc::c_int,
+                grp as libc::c_int,
+                PERF_FLAG_FD_CLOEXEC,
+            )
+        };
+        if fd < 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        Ok(fd as i32)
+    }
+
+    impl Ctr {
+        /// Opens counters of the available events.
+        pub fn open() -> Result<Self> {
+            let mut ret = Ctr {
+                fds: Vec::new(),
+                evts: Vec::new(),
+            };
+            let mut err_ldr = None;
+            for evt in EVTS {
+                let grp = ret.fds.first().copied().unwrap_or(-1);
+                match evt_open(evt, grp) {
+                    Ok(fd) => {
+                        ret.fds.push(fd);
+                        ret.evts.push(evt);
+                    }
+                    Err(err) if grp == -1 => err_ldr = Some(err),
+                    // A missing member event leaves the others counting.
+                    Err(_) => {}
+                }
+            }
+            if ret.fds.is_empty() {
+                match err_ldr {
+                    Some(err) => bail!("perf_event_open failed: {}", err),
+                    None => bail!("perf_event_open failed"),
+                }
+            }
+            Ok(ret)
+        }
+
+        /// Resets and starts counting.
+        #[inline(always)]
+        pub fn start(&self) {
+            // SAFETY: the leader descriptor is open for the life of `self`.
+            unsafe {
+                libc::ioctl(self.fds[0], PERF_EVENT_IOC_RESET as _, PERF_IOC_FLAG_GROUP);
+                libc::ioctl(self.fds[0], PERF_EVENT_IOC_ENABLE as _, PERF_IOC_FLAG_GROUP);
+            }
+        }
+
+        /// Stops counting.
+        #[inline(always)]
+        pub fn stop(&self) {
+            // SAFETY: the leader descriptor is open for the life of `self`.
+            unsafe {
+                libc::ioctl(
+                    self.fds[0],
+                    PERF_EVENT_IOC_DISABLE as _,
+                    PERF_IOC_FLAG_GROUP,
+                );
+            }
+        }
+
+        /// Returns the counts between the last start and stop.
+        pub fn read(&self) -> Ctrs {
+            // Group format: the number of events followed by a value per event.
+            let mut buf = vec![0u64; self.fds.len() + 1];
+            // SAFETY: the buffer holds as many bytes as passed.
+            let cnt = unsafe {
+                libc::read(
+                    self.fds[0],
+                    buf.as_mut_ptr() as *mut libc::c_void,
+                    buf.len() * std::mem::size_of::<u64>(),
+                )
+            };
+            let mut ret = Ctrs::default();
+            if cnt > 0 {
+                for (evt, val) in self.evts.iter().zip(buf[1..].iter()) {
+                    ret.set(*evt, *val);
+                }
+            }
+            ret
+        }
+    }
+
+    impl Drop for Ctr {
+        fn drop(&mut self) {
+            for fd in self.fds.iter() {
+                // SAFETY: each descriptor was opened by `evt_open` and is closed once.
+                unsafe {
+                    libc::close(*fd);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+impl Ctr {
+    /// Opens counters of the available events.
+    pub fn open() -> Result<Self> {
+        anyhow::bail!("hardware counters need Linux perf_event_open")
+    }
+
+    /// Resets and starts counting.
+    #[inline(always)]
+    pub fn start(&self) {}
+
+    /// Stops counting.
+    #[inline(always)]
+    pub fn stop(&self) {}
+
+    /// Returns the counts between the last start and stop.
+    pub fn read(&self) -> Ctrs {
+        Ctrs::default()
+    }
+}
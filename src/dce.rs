@@ -0,0 +1,71 @@
+use crate::*;
+
+/// Minimum growth of the struct label value across a series for the flat check.
+pub const FLT_LEN_MIN: f64 = 8.0;
+
+/// Growth of median cycles at or below which a series is flat.
+pub const FLT_CYC_MAX: f64 = 1.5;
+
+/// Cycles per element at the largest length below which a flat series is flagged.
+///
+/// Keeps series dominated by a fixed cost, such as spawning threads, from being flagged.
+pub const FLT_ELM_MAX: f64 = 0.5;
+
+/// Cycles per element below which work was likely optimized away.
+///
+/// Even wide vector code touching every element needs more than this.
+pub const ELM_FLR: f64 = 0.02;
+
+/// Returns a warning when a series looks optimized away, such as by dead code elimination.
+///
+/// A series is flagged when its corrected median cycles stay flat and cheap per element while
+/// its struct label value, such as `Len`, grows, or when any length costs fewer cycles per
+/// element than `ELM_FLR`. Samples and overhead are both per invocation of a batch, so the
+/// timer overhead doesn't hide an empty body.
+pub fn dce_wrn<L: Label>(res: &SerRes<L>) -> Option<String> {
+    let mdns: Vec<(L, f64, f64)> = res
+        .cels
+        .iter()
+        .filter_map(|(lbl, smps)| {
+            let mdn = mdn(&res.ovh(*lbl).cor(smps));
+            Some((*lbl, lbl.val().ok()? as f64, mdn))
+        })
+        .filter(|(_, val, _)| *val > 0.0)
+        .collect();
+    let lbls = lbls_str(&res.lbls);
+
+    // Per element floor.
+    for (lbl, val, mdn) in mdns.iter() {
+        if mdn / val < ELM_FLR {
+            return Some(format!(
+                "'{}' may be optimized away: {:.3} cycles per element at {}, below the floor of {}",
+                lbls,
+                mdn / val,
+                lbl,
+                ELM_FLR
+            ));
+        }
+    }
+
+    // Flat across lengths.
+    let fst = mdns.iter().min_by(|a, b| a.1.total_cmp(&b.1))?;
+    let lst = mdns.iter().max_by(|a, b| a.1.total_cmp(&b.1))?;
+    let gro_len = lst.1 / fst.1;
+    let gro_cyc = lst.2 / fst.2.max(1.0);
+    if mdns.len() >= 3
+        && gro_len >= FLT_LEN_MIN
+        && gro_cyc <= FLT_CYC_MAX
+        && lst.2 / lst.1 < FLT_ELM_MAX
+    {
+        return Some(format!(
+            "'{}' may be optimized away: cycles grow {}x while length grows {}x from {} to {}",
+            lbls,
+            fmt_rto(gro_cyc),
+            fmt_rto(gro_len),
+            fst.0,
+            lst.0
+        ));
+    }
+
+    None
+}
@@ -0,0 +1,220 @@
+use crate::*;
+use anyhow::{bail, Result};
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::Table;
+use std::path::Path;
+
+/// Default percentage change beyond which a significant regression fails a comparison.
+pub const THR_DFT: f64 = 5.0;
+
+/// Significance level of the rank-sum test.
+pub const ALPHA: f64 = 0.05;
+
+/// Change of one benchmark function between a baseline and a current run.
+#[derive(Debug, Clone)]
+pub struct Dif {
+    /// Labels of the series, such as `alc,rsz,vct`.
+    pub lbls: String,
+    /// Struct label of the function, such as `len(4096)`.
+    pub lbl: String,
+    /// Baseline corrected median cycles.
+    pub bas: f64,
+    /// Current corrected median cycles.
+    pub cur: f64,
+    /// Percentage change of the corrected median from baseline to current.
+    pub pct: f64,
+    /// Two-sided p-value of the Mann-Whitney U test on the corrected samples.
+    pub p: f64,
+}
+
+impl Dif {
+    /// Returns true when the current run is significantly slower.
+    pub fn is_reg(&self) -> bool {
+        self.pct > 0.0 && self.p < ALPHA
+    }
+
+    /// Returns true when the current run is significantly faster.
+    pub fn is_imp(&self) -> bool {
+        self.pct < 0.0 && self.p < ALPHA
+    }
+}
+
+/// Returns the changes of benchmark functions present in both runs.
+///
+/// Functions are matched by label set and struct label. Samples are per invocation less the
+/// timer overhead saved with each run, so a change of batch factor or overhead between runs
+/// isn't judged as a change of the function.
+pub fn difs(bas: &Run, cur: &Run) -> Vec<Dif> {
+    let mut ret = Vec::new();
+    for ser_cur in cur.sers.iter() {
+        let ser_bas = match bas.sers.iter().find(|ser| ser.lbls == ser_cur.lbls) {
+            Some(ser) => ser,
+            None => continue,
+        };
+        for cel_cur in ser_cur.cels.iter() {
+            let cel_bas = match ser_bas.cels.iter().find(|cel| cel.lbl == cel_cur.lbl) {
+                Some(cel) => cel,
+                None => continue,
+            };
+            let smps_bas = cel_bas.ovh.cor(&cel_bas.smps);
+            let smps_cur = cel_cur.ovh.cor(&cel_cur.smps);
+            let bas_mdn = mdn(&smps_bas);
+            let cur_mdn = mdn(&smps_cur);
+            ret.push(Dif {
+                lbls: ser_cur.lbls.clone(),
+                lbl: cel_cur.lbl.clone(),
+                bas: bas_mdn,
+                cur: cur_mdn,
+                pct: (cur_mdn - bas_mdn) / bas_mdn.max(CYC_MIN) * 100.0,
+                p: mwu_p(&smps_bas, &smps_cur),
+            });
+        }
+    }
+    ret
+}
+
+/// Compares two saved runs and prints the change of each benchmark function.
+///
+/// Fails when a significant regression exceeds a percentage threshold.
+pub fn cmp_runs(pth_bas: &Path, pth_cur: &Path, thr: f64) -> Result<()> {
+    let bas = Run::read(pth_bas)?;
+    let cur = Run::read(pth_cur)?;
+
+    println!(
+        "baseline: {} {} {}",
+        bas.mta.git, bas.mta.rustc, bas.mta.cpu
+    );
+    println!(
+        "current:  {} {} {}",
+        cur.mta.git, cur.mta.rustc, cur.mta.cpu
+    );
+
+    let difs = difs(&bas, &cur);
+    if difs.is_empty() {
+        bail!("no label sets and lengths are common to both runs");
+    }
+    println!("{}", dif_tbl(&difs, thr));
+
+    let regs = difs
+        .iter()
+        .filter(|dif| dif.is_reg() && dif.pct > thr)
+        .count();
+    if regs != 0 {
+        bail!("{} significant regression(s) exceed {}%", regs, thr);
+    }
+
+    Ok(())
+}
+
+/// Returns a table of changes between two runs.
+pub fn dif_tbl(difs: &[Dif], thr: f64) -> Table {
+    let mut tbl = Table::new();
+    tbl.load_preset(UTF8_FULL);
+    tbl.set_header(vec![
+        "lbls", "lbl", "baseline", "current", "change", "p", "status",
+    ]);
+    for dif in difs.iter() {
+        let sts = if dif.is_reg() && dif.pct > thr {
+            "regression"
+        } else if dif.is_imp() && -dif.pct > thr {
+            "improvement"
+        } else {
+            ""
+        };
+        tbl.add_row(vec![
+            dif.lbls.clone(),
+            dif.lbl.clone(),
+            fmt_cyc(dif.bas),
+            fmt_cyc(dif.cur),
+            format!("{:+.1}%", dif.pct),
+            format!("{:.3}", dif.p),
+            sts.to_string(),
+        ]);
+    }
+    tbl
+}
+
+/// Returns the two-sided p-value of the Mann-Whitney U test.
+///
+/// Uses the normal approximation with a tie correction.
+pub fn mwu_p(a: &[f64], b: &[f64]) -> f64 {
+    let (n_a, n_b) = (a.len() as f64, b.len() as f64);
+    if a.is_empty() || b.is_empty() {
+        return 1.0;
+    }
+
+    // Rank the pooled samples, averaging ranks of ties.
+    let mut all: Vec<(f64, bool)> = a
+        .iter()
+        .map(|x| (*x, true))
+        .chain(b.iter().map(|x| (*x, false)))
+        .collect();
+    all.sort_unstable_by(|x, y| x.0.total_cmp(&y.0));
+    let mut rnk_a = 0.0;
+    let mut tie = 0.0;
+    let mut idx = 0;
+    while idx < all.len() {
+        let mut end = idx;
+        while end + 1 < all.len() && all[end + 1].0 == all[idx].0 {
+            end += 1;
+        }
+        let rnk = (idx + end) as f64 / 2.0 + 1.0;
+        let cnt = (end - idx + 1) as f64;
+        tie += cnt.powi(3) - cnt;
+        rnk_a += all[idx..=end].iter().filter(|(_, is_a)| *is_a).count() as f64 * rnk;
+        idx = end + 1;
+    }
+
+    let u = rnk_a - n_a * (n_a + 1.0) / 2.0;
+    let n = n_a + n_b;
+    let var = n_a * n_b / 12.0 * ((n + 1.0) - tie / (n * (n - 1.0)));
+    if var <= 0.0 {
+        return 1.0;
+    }
+    let z = (u - n_a * n_b / 2.0).abs() / var.sqrt();
+    erfc(z / std::f64::consts::SQRT_2)
+}
+
+/// Returns the complementary error function.
+///
+/// Numerical Recipes approximation with a fractional error below 1.2e-7.
+pub fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let ret = t
+        * (-z * z - 1.26551223
+            + t * (1.00002368
+                + t * (0.37409196
+                    + t * (0.09678418
+                        + t * (-0.18628806
+                            + t * (0.27886807
+                                + t * (-1.13520398
+                                    + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277)))))))))
+            .exp();
+    if x >= 0.0 {
+        ret
+    } else {
+        2.0 - ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mwu() {
+        // Normal approximations of the two-sided p-value without continuity correction.
+        let p = mwu_p(&[1.0, 2.0, 3.0, 4.0, 5.0], &[6.0, 7.0, 8.0, 9.0, 10.0]);
+        assert!((p - 0.009023).abs() < 1e-5);
+        assert!((mwu_p(&[1.0, 3.0, 5.0], &[2.0, 4.0, 6.0]) - 0.512691).abs() < 1e-5);
+    }
+
+    #[test]
+    fn mwu_eq() {
+        assert_eq!(mwu_p(&[7.0, 7.0, 7.0], &[7.0, 7.0, 7.0]), 1.0);
+        assert_eq!(mwu_p(&[], &[1.0, 2.0]), 1.0);
+        let (a, b) = ([1.0, 2.0, 3.0], [3.0, 2.0, 1.0]);
+        assert_eq!(mwu_p(&a, &[1.0, 2.0, 3.0]), mwu_p(&b, &[1.0, 2.0, 3.0]));
+    }
+}
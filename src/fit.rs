@@ -0,0 +1,214 @@
+use crate::*;
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::Table;
+use serde::{Deserialize, Serialize};
+
+/// Minimum points of a fit with a log term.
+pub const FIT_LOG_MIN: usize = 5;
+
+/// Share of the relative error of the linear fit below which a log term must bring it.
+///
+/// A log term trades off against the constant, so it's only kept when it clearly helps.
+pub const FIT_LOG_GAN: f64 = 0.75;
+
+/// A least-squares fit of corrected median cycles over a struct label, such as `len`.
+///
+/// `cyc = cst + lin * x + log * log2(x)`, where the log term is optional.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Fit {
+    /// Fixed cycles, independent of the struct label.
+    pub cst: f64,
+    /// Cycles per unit of the struct label, such as per element.
+    pub lin: f64,
+    /// Cycles per doubling of the struct label, when the log term improves the fit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log: Option<f64>,
+    /// Weighted coefficient of determination.
+    pub r2: f64,
+    /// Root mean square of residuals relative to the median.
+    pub rms: f64,
+    /// Points fitted.
+    pub cnt: usize,
+}
+
+impl Fit {
+    /// Returns the fit of a series, or `None` with fewer than three reliable points.
+    ///
+    /// Medians span orders of magnitude across a sweep, so residuals are weighted by the
+    /// inverse square of each median; the fit minimizes relative error and small lengths
+    /// still decide the fixed cost. Medians below the noise floor are skipped. The log term
+    /// is kept when it raises the adjusted coefficient of determination and cuts the
+    /// relative error below `FIT_LOG_GAN` of the linear fit.
+    pub fn new<L: Label>(res: &SerRes<L>) -> Option<Self> {
+        let pts: Vec<(f64, f64)> = res
+            .cels
+            .iter()
+            .filter_map(|(lbl, smps)| {
+                let x = lbl.val().ok()? as f64;
+                let ovh = res.ovh(*lbl);
+                let y = mdn(&ovh.cor(smps));
+                (x > 0.0 && !ovh.is_unr(y)).then_some((x, y))
+            })
+            .collect();
+        let lin = wls(&pts, &[|_| 1.0, |x| x])?;
+        let log = (pts.len() >= FIT_LOG_MIN)
+            .then(|| wls(&pts, &[|_| 1.0, |x| x, |x| x.log2()]))
+            .flatten()
+            .filter(|log| {
+                log.r2_adj(pts.len()) > lin.r2_adj(pts.len()) && log.rms < lin.rms * FIT_LOG_GAN
+            });
+        Some(log.unwrap_or(lin))
+    }
+
+    /// Returns the coefficient of determination adjusted for the number of terms.
+    fn r2_adj(&self, cnt: usize) -> f64 {
+        let trms = if self.log.is_some() { 2.0 } else { 1.0 };
+        let cnt = cnt as f64;
+        1.0 - (1.0 - self.r2) * (cnt - 1.0) / (cnt - trms - 1.0)
+    }
+}
+
+/// Returns a weighted least-squares fit of points over basis functions, a constant, a
+/// linear and optionally a log term, or `None` when underdetermined or singular.
+fn wls(pts: &[(f64, f64)], fns: &[fn(f64) -> f64]) -> Option<Fit> {
+    let trms = fns.len();
+    if pts.len() <= trms {
+        return None;
+    }
+    let wgt = |y: f64| 1.0 / y.max(CYC_MIN).powi(2);
+
+    // Normal equations `A^T W A c = A^T W y`, with the right side as the last column.
+    let mut mat = vec![vec![0.0; trms + 1]; trms];
+    for (x, y) in pts.iter() {
+        let row: Vec<f64> = fns.iter().map(|f| f(*x)).collect();
+        for r in 0..trms {
+            for c in 0..trms {
+                mat[r][c] += wgt(*y) * row[r] * row[c];
+            }
+            mat[r][trms] += wgt(*y) * row[r] * y;
+        }
+    }
+    let cfs = slv(mat)?;
+
+    // Goodness of fit with the same weights.
+    let prd = |x: f64| {
+        fns.iter()
+            .zip(cfs.iter())
+            .map(|(f, c)| c * f(x))
+            .sum::<f64>()
+    };
+    let wgt_sum: f64 = pts.iter().map(|(_, y)| wgt(*y)).sum();
+    let mean = pts.iter().map(|(_, y)| wgt(*y) * y).sum::<f64>() / wgt_sum;
+    let ss_res: f64 = pts
+        .iter()
+        .map(|(x, y)| wgt(*y) * (y - prd(*x)).powi(2))
+        .sum();
+    let ss_tot: f64 = pts.iter().map(|(_, y)| wgt(*y) * (y - mean).powi(2)).sum();
+    Some(Fit {
+        cst: cfs[0],
+        lin: cfs[1],
+        log: cfs.get(2).copied(),
+        r2: if ss_tot > 0.0 {
+            1.0 - ss_res / ss_tot
+        } else {
+            1.0
+        },
+        rms: (ss_res / pts.len() as f64).sqrt(),
+        cnt: pts.len(),
+    })
+}
+
+/// Solves a linear system of an augmented matrix by Gaussian elimination with partial
+/// pivoting.
+fn slv(mut mat: Vec<Vec<f64>>) -> Option<Vec<f64>> {
+    let cnt = mat.len();
+    for col in 0..cnt {
+        let piv = (col..cnt).max_by(|a, b| mat[*a][col].abs().total_cmp(&mat[*b][col].abs()))?;
+        if mat[piv][col].abs() < f64::EPSILON {
+            return None;
+        }
+        mat.swap(col, piv);
+        let piv = mat[col].clone();
+        for row in mat.iter_mut().skip(col + 1) {
+            let fct = row[col] / piv[col];
+            for (val, val_piv) in row.iter_mut().zip(piv.iter()).skip(col) {
+                *val -= fct * val_piv;
+            }
+        }
+    }
+    let mut ret = vec![0.0; cnt];
+    for row in (0..cnt).rev() {
+        let sum: f64 = (row + 1..cnt).map(|c| mat[row][c] * ret[c]).sum();
+        ret[row] = (mat[row][cnt] - sum) / mat[row][row];
+    }
+    Some(ret)
+}
+
+/// Returns a table of the cost fit of each series, separating fixed cycles from cycles
+/// per element. Series with too few reliable points to fit are left out.
+pub fn fit_tbl<L: Label>(ress: &[&SerRes<L>]) -> Table {
+    let mut tbl = Table::new();
+    tbl.load_preset(UTF8_FULL);
+    let nam = ress
+        .iter()
+        .find_map(|res| res.cels.first())
+        .map(|(lbl, _)| format!("{:#}", lbl))
+        .unwrap_or_default();
+    tbl.set_header(vec![
+        String::from("fit"),
+        String::from("fixed"),
+        format!("per {}", nam),
+        format!("per log2({})", nam),
+        String::from("R²"),
+        String::from("rms error"),
+    ]);
+    for res in ress.iter() {
+        let Some(fit) = Fit::new(res) else {
+            continue;
+        };
+        tbl.add_row(vec![
+            lbls_str(&res.lbls),
+            fmt_sgn(fit.cst),
+            fmt_sgn(fit.lin),
+            fit.log.map(fmt_sgn).unwrap_or_else(|| String::from("-")),
+            format!("{:.3}", fit.r2),
+            format!("{:.1}%", fit.rms * 100.0),
+        ]);
+    }
+    tbl
+}
+
+/// Returns a signed decimal with three significant digits.
+fn fmt_sgn(val: f64) -> String {
+    if val < 0.0 {
+        format!("-{}", fmt_dec(-val))
+    } else {
+        fmt_dec(val)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wls_lin() {
+        let pts: Vec<(f64, f64)> = [16.0, 64.0, 256.0, 1024.0]
+            .iter()
+            .map(|x| (*x, 100.0 + 2.0 * x))
+            .collect();
+        let fit = wls(&pts, &[|_| 1.0, |x| x]).unwrap();
+        assert!((fit.cst - 100.0).abs() < 1e-6);
+        assert!((fit.lin - 2.0).abs() < 1e-9);
+        assert_eq!(fit.log, None);
+        assert!((fit.r2 - 1.0).abs() < 1e-9);
+        assert!(fit.rms < 1e-9);
+    }
+
+    #[test]
+    fn wls_und() {
+        assert!(wls(&[(1.0, 2.0), (2.0, 4.0)], &[|_| 1.0, |x| x]).is_none());
+        // Repeated lengths leave the linear term undetermined.
+        assert!(wls(&[(4.0, 9.0), (4.0, 9.0), (4.0, 9.0)], &[|_| 1.0, |x| x]).is_none());
+    }
+}
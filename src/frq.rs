@@ -0,0 +1,209 @@
+use crate::*;
+use anyhow::{bail, Error, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::OnceLock;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Duration of calibrating the time-stamp counter against the monotonic clock.
+pub const FRQ_CAL: Duration = Duration::from_millis(100);
+
+/// Relative difference within which a reported frequency agrees with the calibrated one.
+pub const FRQ_TOL: f64 = 0.02;
+
+static FRQ: OnceLock<Frq> = OnceLock::new();
+
+static UNT: AtomicU8 = AtomicU8::new(Unt::Cyc as u8);
+
+/// Frequency of the time-stamp counter.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Frq {
+    /// Ticks per second.
+    pub hz: f64,
+    /// Where the frequency came from.
+    pub src: FrqSrc,
+    /// Whether the counter ticks at a constant rate through frequency changes and sleep
+    /// states, from the `constant_tsc` and `nonstop_tsc` flags.
+    pub inv: bool,
+}
+
+/// Source of the time-stamp counter frequency.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FrqSrc {
+    /// CPUID leaf 0x15, the crystal clock times the counter ratio.
+    Cpuid,
+    /// Nominal frequency in the CPU model name of `/proc/cpuinfo`, such as `@ 2.40GHz`.
+    Cpuinfo,
+    /// Ticks counted over `FRQ_CAL` of `CLOCK_MONOTONIC`.
+    Cal,
+    /// The timer reads nanoseconds on targets without a time-stamp counter.
+    Clk,
+}
+
+impl Frq {
+    /// Detects the frequency and invariance of the time-stamp counter.
+    ///
+    /// The counter is always calibrated against the monotonic clock. A frequency reported
+    /// by CPUID or the CPU model name is preferred when it agrees with the calibration
+    /// within `FRQ_TOL`, since it isn't subject to scheduling noise.
+    pub fn dtc() -> Self {
+        if cfg!(not(target_arch = "x86_64")) {
+            return Self {
+                hz: 1e9,
+                src: FrqSrc::Clk,
+                inv: true,
+            };
+        }
+        let hz_cal = frq_cal();
+        let (hz, src) = [
+            (frq_cpuid(), FrqSrc::Cpuid),
+            (frq_cpuinfo(), FrqSrc::Cpuinfo),
+        ]
+        .into_iter()
+        .find_map(|(hz, src)| {
+            hz.filter(|hz| (hz - hz_cal).abs() <= hz_cal * FRQ_TOL)
+                .map(|hz| (hz, src))
+        })
+        .unwrap_or((hz_cal, FrqSrc::Cal));
+        Self {
+            hz,
+            src,
+            inv: tsc_inv(),
+        }
+    }
+
+    /// Returns nanoseconds of counter ticks.
+    pub fn ns(&self, cyc: f64) -> f64 {
+        cyc * 1e9 / self.hz
+    }
+}
+
+impl fmt::Display for Frq {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let src = match self.src {
+            FrqSrc::Cpuid => "CPUID",
+            FrqSrc::Cpuinfo => "/proc/cpuinfo",
+            FrqSrc::Cal => "calibrated",
+            FrqSrc::Clk => "monotonic clock",
+        };
+        write!(
+            f,
+            "TSC frequency: {:.3} GHz ({}), {}",
+            self.hz / 1e9,
+            src,
+            if self.inv {
+                "invariant"
+            } else {
+                "not invariant"
+            }
+        )
+    }
+}
+
+/// Returns the frequency of the time-stamp counter, detected on first use.
+pub fn frq() -> &'static Frq {
+    FRQ.get_or_init(Frq::dtc)
+}
+
+/// Returns the frequency from CPUID leaf 0x15, when the CPU enumerates its crystal clock.
+#[cfg(target_arch = "x86_64")]
+fn frq_cpuid() -> Option<f64> {
+    use std::arch::x86_64::__cpuid;
+    if __cpuid(0).eax < 0x15 {
+        return None;
+    }
+    let leaf = __cpuid(0x15);
+    (leaf.eax != 0 && leaf.ebx != 0 && leaf.ecx != 0)
+        .then(|| leaf.ecx as f64 * leaf.ebx as f64 / leaf.eax as f64)
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn frq_cpuid() -> Option<f64> {
+    None
+}
+
+/// Returns the nominal frequency in the CPU model name, such as `@ 2.40GHz`.
+fn frq_cpuinfo() -> Option<f64> {
+    let txt = fs::read_to_string("/proc/cpuinfo").ok()?;
+    let nam = txt.lines().find(|lne| lne.starts_with("model name"))?;
+    let ghz = nam.rsplit_once('@')?.1.trim().strip_suffix("GHz")?;
+    ghz.trim().parse::<f64>().ok().map(|ghz| ghz * 1e9)
+}
+
+/// Returns the frequency counted over `FRQ_CAL` of the monotonic clock.
+fn frq_cal() -> f64 {
+    let bgn = Instant::now();
+    let tsc_bgn = tsc();
+    thread::sleep(FRQ_CAL);
+    let tsc_end = tsc();
+    let elp = bgn.elapsed();
+    tsc_end.saturating_sub(tsc_bgn) as f64 / elp.as_secs_f64()
+}
+
+/// Returns whether the time-stamp counter is invariant.
+///
+/// Reads the `constant_tsc` and `nonstop_tsc` flags of `/proc/cpuinfo`, falling back to
+/// the invariant TSC bit of CPUID leaf 0x80000007.
+fn tsc_inv() -> bool {
+    if let Ok(txt) = fs::read_to_string("/proc/cpuinfo") {
+        if let Some(flgs) = txt.lines().find(|lne| lne.starts_with("flags")) {
+            let has = |flg: &str| flgs.split_whitespace().any(|x| x == flg);
+            return has("constant_tsc") && has("nonstop_tsc");
+        }
+    }
+    tsc_inv_cpuid()
+}
+
+#[cfg(target_arch = "x86_64")]
+fn tsc_inv_cpuid() -> bool {
+    use std::arch::x86_64::__cpuid;
+    __cpuid(0x8000_0000).eax >= 0x8000_0007 && __cpuid(0x8000_0007).edx & (1 << 8) != 0
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn tsc_inv_cpuid() -> bool {
+    true
+}
+
+/// Units of times shown in tables.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Unt {
+    /// Time-stamp counter cycles.
+    #[default]
+    Cyc,
+    /// Nanoseconds at the detected counter frequency.
+    Ns,
+    /// Cycles above nanoseconds.
+    Both,
+}
+
+impl FromStr for Unt {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "cycles" => Ok(Unt::Cyc),
+            "ns" => Ok(Unt::Ns),
+            "both" => Ok(Unt::Both),
+            _ => bail!("unknown unit '{}', expected cycles, ns or both", s),
+        }
+    }
+}
+
+/// Sets the units of times shown in tables.
+pub fn set_unt(unt: Unt) {
+    UNT.store(unt as u8, Ordering::Relaxed);
+}
+
+/// Returns the units of times shown in tables.
+pub fn unt() -> Unt {
+    match UNT.load(Ordering::Relaxed) {
+        x if x == Unt::Ns as u8 => Unt::Ns,
+        x if x == Unt::Both as u8 => Unt::Both,
+        _ => Unt::Cyc,
+    }
+}
@@ -0,0 +1,30 @@
+use anyhow::Result;
+use std::fmt;
+use std::hash::Hash;
+
+/// A benchmark label.
+///
+/// Labels are grouped into label sets which identify a series of benchmark functions.
+pub trait Label: fmt::Debug + fmt::Display + Copy + Eq + Ord + Hash + EnumStructVal {}
+
+/// Returns the value held by a struct enum case.
+pub trait EnumStructVal {
+    fn val(&self) -> Result<u32>;
+}
+
+/// Returns a label set as a comma separated string.
+pub fn lbls_str<L: Label>(lbls: &[L]) -> String {
+    let mut lbls = lbls.to_vec();
+    lbls.sort();
+    lbls.iter()
+        .map(|lbl| lbl.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Returns true when a label matches a label token.
+///
+/// A struct label token without a value, such as `acm`, matches any value.
+pub fn lbl_mat<L: Label>(lbl: &L, tok: &str) -> bool {
+    lbl.to_string() == tok || format!("{:#}", lbl) == tok
+}
@@ -0,0 +1,155 @@
+use anyhow::{bail, Context, Error, Result};
+use std::str::FromStr;
+use std::sync::Mutex;
+
+/// Lengths given by `--len`, replacing the manifest lengths of runtime length studies.
+static LENS: Mutex<Option<Vec<usize>>> = Mutex::new(None);
+
+/// A range of runtime lengths, such as `1k..64M:x2`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct LenRng {
+    /// First length.
+    pub fst: usize,
+    /// Last length, included when a step lands on it.
+    pub lst: usize,
+    /// Step between lengths.
+    pub stp: LenStp,
+}
+
+/// Step of a range of runtime lengths.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LenStp {
+    /// Multiplies by a factor, such as `:x2`.
+    Mul(usize),
+    /// Adds a step, such as `:+100`.
+    Add(usize),
+}
+
+impl LenRng {
+    /// Returns the lengths of the range in ascending order.
+    pub fn lens(&self) -> Vec<usize> {
+        let mut ret: Vec<usize> = Vec::new();
+        let mut len = Some(self.fst);
+        while let Some(cur) = len.filter(|len| *len <= self.lst) {
+            ret.push(cur);
+            len = match self.stp {
+                LenStp::Mul(fct) => cur.checked_mul(fct),
+                LenStp::Add(stp) => cur.checked_add(stp),
+            };
+        }
+        ret
+    }
+}
+
+impl FromStr for LenRng {
+    type Err = Error;
+
+    /// Parses `<FIRST>[..<LAST>[:x<FACTOR>|:+<STEP>]]`, where the step defaults to `:x2`
+    /// and each number may end in a binary `k`, `M` or `G` suffix.
+    fn from_str(s: &str) -> Result<Self> {
+        let (rng, stp) = match s.split_once(':') {
+            Some((rng, stp)) => (rng, Some(stp)),
+            None => (s, None),
+        };
+        let (fst, lst) = match rng.split_once("..") {
+            Some((fst, lst)) => (prs_len(fst)?, prs_len(lst)?),
+            None => (prs_len(rng)?, prs_len(rng)?),
+        };
+        let stp = match stp {
+            None => LenStp::Mul(2),
+            Some(stp) => match (stp.strip_prefix('x'), stp.strip_prefix('+')) {
+                (Some(fct), _) => LenStp::Mul(prs_len(fct)?),
+                (_, Some(stp)) => LenStp::Add(prs_len(stp)?),
+                _ => bail!("length step '{}' isn't in the form xN or +N", stp),
+            },
+        };
+        if fst == 0 || lst < fst {
+            bail!("length range '{}' needs 0 < first <= last", s);
+        }
+        if lst > u32::MAX as usize {
+            bail!("length range '{}' ends beyond {}", s, u32::MAX);
+        }
+        match stp {
+            LenStp::Mul(fct) if fct < 2 => bail!("length factor of '{}' must be at least 2", s),
+            LenStp::Add(0) => bail!("length step of '{}' must be positive", s),
+            _ => {}
+        }
+        Ok(Self { fst, lst, stp })
+    }
+}
+
+/// Parses a length with an optional binary suffix, such as `64M`.
+fn prs_len(s: &str) -> Result<usize> {
+    let (num, scl) = [('k', 1 << 10), ('M', 1 << 20), ('G', 1 << 30)]
+        .into_iter()
+        .find_map(|(sfx, scl)| s.strip_suffix(sfx).map(|num| (num, scl)))
+        .unwrap_or((s, 1));
+    num.parse::<usize>()
+        .ok()
+        .and_then(|num| num.checked_mul(scl))
+        .with_context(|| format!("invalid length '{}'", s))
+}
+
+/// Sets the lengths of runtime length studies to the union of ranges.
+///
+/// Without ranges, studies keep the lengths of their manifest range.
+pub fn set_lens(rngs: &[LenRng]) {
+    let mut lens: Vec<usize> = rngs.iter().flat_map(|rng| rng.lens()).collect();
+    lens.sort_unstable();
+    lens.dedup();
+    let mut cur = LENS.lock().unwrap_or_else(|err| err.into_inner());
+    *cur = (!lens.is_empty()).then_some(lens);
+}
+
+/// Returns the lengths of a runtime length study, those set by `--len` or else its
+/// manifest lengths.
+pub fn rtm_lens(dft: &[usize]) -> Vec<usize> {
+    let cur = LENS.lock().unwrap_or_else(|err| err.into_inner());
+    cur.clone().unwrap_or_else(|| dft.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rng_mul() {
+        let rng: LenRng = "1k..64M:x2".parse().unwrap();
+        assert_eq!(
+            rng,
+            LenRng {
+                fst: 1 << 10,
+                lst: 64 << 20,
+                stp: LenStp::Mul(2)
+            }
+        );
+        let lens = rng.lens();
+        assert_eq!(lens.len(), 17);
+        assert_eq!(lens.first(), Some(&1024));
+        assert_eq!(lens.last(), Some(&(64 << 20)));
+    }
+
+    #[test]
+    fn rng_add() {
+        let rng: LenRng = "100..500:+100".parse().unwrap();
+        assert_eq!(rng.lens(), vec![100, 200, 300, 400, 500]);
+        // The last length is left out when no step lands on it.
+        let rng: LenRng = "1..10:x4".parse().unwrap();
+        assert_eq!(rng.lens(), vec![1, 4]);
+    }
+
+    #[test]
+    fn rng_dft() {
+        assert_eq!("4k".parse::<LenRng>().unwrap().lens(), vec![4096]);
+        assert_eq!("1..8".parse::<LenRng>().unwrap().stp, LenStp::Mul(2));
+    }
+
+    #[test]
+    fn rng_err() {
+        for s in [
+            "", "x", "0..8", "8..4", "1..8:x1", "1..8:+0", "1..8:2", "1..8G", "1k..2q",
+        ] {
+            assert!(s.parse::<LenRng>().is_err(), "'{}' parsed", s);
+        }
+    }
+}
@@ -0,0 +1,49 @@
+//! Measures code performance in CPU cycles.
+
+mod adp;
+mod afn;
+mod bat;
+mod cal;
+mod cli;
+mod ctr;
+mod dce;
+mod dif;
+mod fit;
+mod frq;
+mod lbl;
+mod len;
+mod out;
+mod pol;
+mod qry;
+mod res;
+mod rnd;
+mod scl;
+mod sta;
+mod stdy;
+mod tbl;
+mod tme;
+mod xov;
+
+pub use adp::*;
+pub use afn::*;
+pub use bat::*;
+pub use cal::*;
+pub use cli::*;
+pub use ctr::*;
+pub use dce::*;
+pub use dif::*;
+pub use fit::*;
+pub use frq::*;
+pub use lbl::*;
+pub use len::*;
+pub use out::*;
+pub use pol::*;
+pub use qry::*;
+pub use res::*;
+pub use rnd::*;
+pub use scl::*;
+pub use sta::*;
+pub use stdy::*;
+pub use tbl::*;
+pub use tme::*;
+pub use xov::*;
@@ -0,0 +1,72 @@
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::hash::Hasher;
+
+/// Maximum number of characters in an output preview.
+pub const PVW_MAX: usize = 48;
+
+/// Return value of a benchmark function, reduced to a digest and a short preview.
+///
+/// Values are compared by their `Debug` text, so `[0u32; 4]` equals `vec![0u32; 4]` and
+/// `6u32` equals `6usize`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Out {
+    /// Hash of the `Debug` text.
+    pub dgst: u64,
+    /// Leading characters of the `Debug` text.
+    pub pvw: String,
+    /// Whether the preview is shorter than the `Debug` text.
+    pub trn: bool,
+}
+
+impl Out {
+    /// Returns the output of a value.
+    ///
+    /// The `Debug` text is streamed into the hash, so large outputs aren't held in memory.
+    pub fn new<T: fmt::Debug>(val: &T) -> Self {
+        let mut ret = Self {
+            dgst: 0,
+            pvw: String::new(),
+            trn: false,
+        };
+        let mut hsh = DefaultHasher::new();
+        let mut wtr = OutWtr {
+            hsh: &mut hsh,
+            out: &mut ret,
+        };
+        // Writing to a hasher can't fail.
+        let _ = fmt::write(&mut wtr, format_args!("{:?}", val));
+        ret.dgst = hsh.finish();
+        ret
+    }
+}
+
+impl fmt::Display for Out {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.trn {
+            write!(f, "{}...", self.pvw)
+        } else {
+            write!(f, "{}", self.pvw)
+        }
+    }
+}
+
+struct OutWtr<'a> {
+    hsh: &'a mut DefaultHasher,
+    out: &'a mut Out,
+}
+
+impl fmt::Write for OutWtr<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.hsh.write(s.as_bytes());
+        for chr in s.chars() {
+            if self.out.pvw.len() < PVW_MAX {
+                self.out.pvw.push(chr);
+            } else {
+                self.out.trn = true;
+                break;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,114 @@
+use crate::*;
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+
+/// Work sent to a worker, returning a boxed result.
+type Job = Box<dyn FnOnce() -> Box<dyn Any + Send> + Send>;
+
+/// Result of a job, or the payload of its panic.
+type Ret = thread::Result<Box<dyn Any + Send>>;
+
+/// Pools by thread count, shared for the life of the process.
+static POLS: Mutex<BTreeMap<usize, &'static Pol>> = Mutex::new(BTreeMap::new());
+
+/// A pool of persistent worker threads.
+///
+/// Workers are spawned once and stay alive across iterations, so dispatching work to them
+/// excludes thread creation. Channels are also created once with the pool. Each worker is
+/// pinned with `pin_wkr` by its index.
+pub struct Pol {
+    /// Worker channels and the channel of results.
+    wkrs: Mutex<Wkrs>,
+}
+
+struct Wkrs {
+    txs: Vec<Sender<Job>>,
+    rx: Receiver<(usize, Ret)>,
+}
+
+/// Returns the shared pool of a thread count, spawning its workers on first use.
+pub fn pol(cnt: usize) -> &'static Pol {
+    let mut pols = POLS.lock().unwrap_or_else(|err| err.into_inner());
+    pols.entry(cnt)
+        .or_insert_with(|| Box::leak(Box::new(Pol::new(cnt))))
+}
+
+impl Pol {
+    /// Returns a pool with a number of spawned workers.
+    pub fn new(cnt: usize) -> Self {
+        assert!(cnt > 0, "a pool needs at least one worker");
+        let (tx_res, rx) = channel::<(usize, Ret)>();
+        let txs = (0..cnt)
+            .map(|idx| {
+                let (tx, rx_job) = channel::<Job>();
+                let tx_res = tx_res.clone();
+                thread::Builder::new()
+                    .name(format!("mtr-pol-{}", idx))
+                    .spawn(move || {
+                        pin_wkr(idx);
+                        // A panicking job is reported to the caller, and the worker stays alive.
+                        for job in rx_job {
+                            let ret = panic::catch_unwind(AssertUnwindSafe(job));
+                            if tx_res.send((idx, ret)).is_err() {
+                                break;
+                            }
+                        }
+                    })
+                    .expect("failed to spawn a pool worker");
+                tx
+            })
+            .collect();
+        Self {
+            wkrs: Mutex::new(Wkrs { txs, rx }),
+        }
+    }
+
+    /// Returns the number of workers.
+    pub fn cnt(&self) -> usize {
+        self.wkrs().txs.len()
+    }
+
+    /// Runs a function on every worker with the worker index.
+    ///
+    /// Returns the results in worker order. When the function panics on any worker, the
+    /// panic resumes on the caller once every worker has finished.
+    pub fn map<T, F>(&self, f: F) -> Vec<T>
+    where
+        T: Send + 'static,
+        F: Fn(usize) -> T + Send + Sync + 'static,
+    {
+        let wkrs = self.wkrs();
+        let f = Arc::new(f);
+        for (idx, tx) in wkrs.txs.iter().enumerate() {
+            let f = f.clone();
+            tx.send(Box::new(move || Box::new(f(idx)) as Box<dyn Any + Send>))
+                .expect("pool worker stopped");
+        }
+
+        let mut ret: Vec<Option<T>> = (0..wkrs.txs.len()).map(|_| None).collect();
+        let mut pnc: Option<Box<dyn Any + Send>> = None;
+        for _ in 0..wkrs.txs.len() {
+            let (idx, val) = wkrs.rx.recv().expect("pool worker stopped");
+            match val {
+                Ok(val) => ret[idx] = val.downcast::<T>().ok().map(|val| *val),
+                Err(err) => pnc = pnc.or(Some(err)),
+            }
+        }
+        if let Some(pnc) = pnc {
+            // Release the workers before unwinding, so the pool stays usable.
+            drop(wkrs);
+            panic::resume_unwind(pnc);
+        }
+        ret.into_iter()
+            .map(|val| val.expect("pool worker returned an unexpected type"))
+            .collect()
+    }
+
+    fn wkrs(&self) -> MutexGuard<'_, Wkrs> {
+        self.wkrs.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
@@ -0,0 +1,81 @@
+use crate::*;
+use anyhow::{bail, Result};
+
+/// Builds a query of label set selections and comparisons.
+#[derive(Debug, Default, Clone)]
+pub struct QryBld {
+    /// Selections of label tokens.
+    pub sels: Vec<Vec<String>>,
+    /// Comparisons of two selection ids.
+    pub cmps: Vec<(usize, usize)>,
+    /// Rankings of any number of selection ids.
+    pub rnks: Vec<Vec<usize>>,
+    /// Selection ids reporting their fastest series per struct label.
+    pub bsts: Vec<usize>,
+}
+
+impl QryBld {
+    /// Returns a new query builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Selects label sets containing every label.
+    ///
+    /// Returns the selection id.
+    pub fn sel<L: Label>(&mut self, lbls: &[L]) -> usize {
+        self.sel_toks(lbls.iter().map(|lbl| lbl.to_string()).collect())
+    }
+
+    /// Selects label sets matching a comma separated label expression, such as `acm,thd(4)`.
+    ///
+    /// Returns the selection id.
+    pub fn sel_str(&mut self, expr: &str) -> Result<usize> {
+        let toks: Vec<String> = expr
+            .split(',')
+            .map(|tok| tok.trim().to_lowercase())
+            .filter(|tok| !tok.is_empty())
+            .collect();
+        if toks.is_empty() {
+            bail!("empty label expression '{}'", expr);
+        }
+        Ok(self.sel_toks(toks))
+    }
+
+    /// Compares two selections.
+    pub fn cmp(&mut self, sel_a: usize, sel_b: usize) {
+        self.cmps.push((sel_a, sel_b));
+    }
+
+    /// Ranks any number of selections in one table.
+    pub fn rnk(&mut self, sels: &[usize]) {
+        self.rnks.push(sels.to_vec());
+    }
+
+    /// Reports the fastest series of a selection per struct label, such as the best
+    /// configuration of a sweep per length.
+    pub fn bst(&mut self, sel: usize) {
+        self.bsts.push(sel);
+    }
+
+    /// Returns true when the query has no selections.
+    pub fn is_empty(&self) -> bool {
+        self.sels.is_empty()
+    }
+
+    fn sel_toks(&mut self, toks: Vec<String>) -> usize {
+        match self.sels.iter().position(|sel| *sel == toks) {
+            Some(id) => id,
+            None => {
+                self.sels.push(toks);
+                self.sels.len() - 1
+            }
+        }
+    }
+}
+
+/// Returns true when a label set contains a match for every selected label token.
+pub fn sel_mat<L: Label>(sel: &[String], lbls: &[L]) -> bool {
+    sel.iter()
+        .all(|tok| lbls.iter().any(|lbl| lbl_mat(lbl, tok)))
+}
@@ -0,0 +1,289 @@
+use crate::*;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Default directory of saved results.
+pub const OUT_DFT: &str = "res";
+
+/// Results of a study run with its metadata.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Run {
+    /// Metadata of the run.
+    pub mta: Mta,
+    /// Results of each series.
+    pub sers: Vec<SerRec>,
+}
+
+/// Metadata of a study run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Mta {
+    /// Seconds since the Unix epoch when the run finished.
+    pub tme: u64,
+    /// Git commit hash of the build.
+    pub git: String,
+    /// Version of the compiler used for the build.
+    pub rustc: String,
+    /// CPU model name.
+    pub cpu: String,
+    /// Iterations per benchmark function.
+    pub itr: u16,
+    /// Seed of runtime random generators.
+    #[serde(default)]
+    pub seed: u64,
+    /// Seed of random literals generated at build time.
+    #[serde(default)]
+    pub bld_seed: u64,
+    /// CPUs the measuring thread may run on.
+    #[serde(default)]
+    pub afn: Vec<usize>,
+    /// CPUs workers of parallel studies are pinned to, empty when unpinned.
+    #[serde(default)]
+    pub wkr_afn: Vec<usize>,
+    /// Stopping rule of adaptive sampling, when `itr` was the minimum iterations.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub adp: Option<Adp>,
+    /// Frequency of the time-stamp counter, which converts cycles to nanoseconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frq: Option<Frq>,
+}
+
+/// Results of one series.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SerRec {
+    /// Labels of the series, such as `alc,rsz,vct`.
+    pub lbls: String,
+    /// Least-squares fit of corrected median cycles over the struct label.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fit: Option<Fit>,
+    /// Results of each benchmark function.
+    pub cels: Vec<CelRec>,
+}
+
+/// Results of one benchmark function.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CelRec {
+    /// Struct label of the function, such as `len(4096)`.
+    pub lbl: String,
+    /// Value of the struct label, such as `4096`.
+    pub val: u32,
+    /// Summary statistics of the samples.
+    pub sta: Sta,
+    /// Calibrated timer overhead of the function.
+    #[serde(default)]
+    pub ovh: Ovh,
+    /// Summary statistics of the samples less the timer overhead.
+    #[serde(default)]
+    pub cor: Sta,
+    /// Whether the corrected median is below the noise floor of the timer.
+    #[serde(default)]
+    pub unr: bool,
+    /// Invocations per sample, whose cycles are divided by it.
+    #[serde(default = "bat_dft")]
+    pub bat: u32,
+    /// Median hardware counts, when counted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ctr: Option<Ctrs>,
+    /// Iterations sampled, which vary per function with adaptive sampling.
+    #[serde(default)]
+    pub itr: usize,
+    /// Cycle samples per invocation, one per iteration.
+    pub smps: Vec<f64>,
+}
+
+impl Run {
+    /// Returns a run record of series results.
+    pub fn new<L: Label>(ress: &[SerRes<L>], itr: u16, seed: u64, adp: Option<Adp>) -> Self {
+        let sers = ress
+            .iter()
+            .map(|res| SerRec {
+                lbls: lbls_str(&res.lbls),
+                fit: Fit::new(res),
+                cels: res
+                    .cels
+                    .iter()
+                    .map(|(lbl, smps)| {
+                        let ovh = res.ovh(*lbl);
+                        let cor = Sta::new(&ovh.cor(smps));
+                        CelRec {
+                            lbl: lbl.to_string(),
+                            val: lbl.val().unwrap_or_default(),
+                            sta: Sta::new(smps),
+                            ovh,
+                            cor,
+                            unr: ovh.is_unr(cor.mdn),
+                            bat: res
+                                .bats
+                                .iter()
+                                .find(|(lbl_bat, _)| lbl_bat == lbl)
+                                .map(|(_, bat)| *bat)
+                                .unwrap_or_else(bat_dft),
+                            ctr: res
+                                .ctrs
+                                .iter()
+                                .find(|(lbl_ctr, _)| lbl_ctr == lbl)
+                                .and_then(|(_, smps)| Ctrs::mdn(smps)),
+                            itr: smps.len(),
+                            smps: smps.clone(),
+                        }
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Self {
+            mta: Mta::new(itr, seed, adp),
+            sers,
+        }
+    }
+
+    /// Reads a run record from a JSON file.
+    pub fn read(pth: &Path) -> Result<Self> {
+        let txt = fs::read_to_string(pth)
+            .with_context(|| format!("failed to read '{}'", pth.display()))?;
+        let mut ret: Self = serde_json::from_str(&txt)
+            .with_context(|| format!("failed to parse '{}'", pth.display()))?;
+        // Results saved before adaptive sampling ran every function for `itr` iterations.
+        for cel in ret.sers.iter_mut().flat_map(|ser| ser.cels.iter_mut()) {
+            if cel.itr == 0 {
+                cel.itr = cel.smps.len();
+            }
+        }
+        Ok(ret)
+    }
+
+    /// Writes the run record to JSON and CSV files in a directory.
+    ///
+    /// Files are named by the finish time of the run, with a numbered suffix when an earlier
+    /// run in the same second took the name. Returns the paths of the written files.
+    pub fn sav(&self, dir: &Path) -> Result<(PathBuf, PathBuf)> {
+        fs::create_dir_all(dir).with_context(|| format!("failed to create '{}'", dir.display()))?;
+        let stm = (0..)
+            .map(|idx| match idx {
+                0 => format!("mtr-{}", self.mta.tme),
+                _ => format!("mtr-{}-{}", self.mta.tme, idx),
+            })
+            .find(|stm| {
+                !dir.join(format!("{}.json", stm)).exists()
+                    && !dir.join(format!("{}.csv", stm)).exists()
+            })
+            .expect("unbounded suffixes");
+
+        let pth_json = dir.join(format!("{}.json", stm));
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(&pth_json, json)
+            .with_context(|| format!("failed to write '{}'", pth_json.display()))?;
+
+        let pth_csv = dir.join(format!("{}.csv", stm));
+        fs::write(&pth_csv, self.csv())
+            .with_context(|| format!("failed to write '{}'", pth_csv.display()))?;
+
+        Ok((pth_json, pth_csv))
+    }
+
+    /// Returns the run record as CSV with one row per benchmark function.
+    ///
+    /// Raw samples are space separated in the last column.
+    pub fn csv(&self) -> String {
+        let mut ret = String::from(concat!(
+            "lbls,lbl,val,min,max,mdn,mean,std,p5,p95,mad,ci_lo,ci_hi,",
+            "ovh,nse,cor_mdn,cor_ci_lo,cor_ci_hi,cor_mdn_ns,unr,bat,itr,",
+            "ins,cyc,brn_mis,l1d_mis,llc_mis,ipc,smps\n"
+        ));
+        for ser in self.sers.iter() {
+            for cel in ser.cels.iter() {
+                let smps: Vec<String> = cel.smps.iter().map(|x| x.to_string()).collect();
+                // Counts which weren't measured are empty.
+                let ctr = cel.ctr.unwrap_or_default();
+                let ctrs: Vec<String> = EVTS
+                    .iter()
+                    .map(|evt| ctr.get(*evt).map(|x| x.to_string()).unwrap_or_default())
+                    .chain([ctr.ipc().map(|x| format!("{:.3}", x)).unwrap_or_default()])
+                    .collect();
+                // Nanoseconds are empty when the counter frequency wasn't saved.
+                let ns = self
+                    .mta
+                    .frq
+                    .map(|frq| format!("{:.3}", frq.ns(cel.cor.mdn)))
+                    .unwrap_or_default();
+                let _ = writeln!(
+                    ret,
+                    concat!(
+                        "\"{}\",{},{},{},{},{},{:.3},{:.3},{},{},{},{},{},",
+                        "{},{:.3},{},{},{},{},{},{},{},{},{}"
+                    ),
+                    ser.lbls,
+                    cel.lbl,
+                    cel.val,
+                    cel.sta.min,
+                    cel.sta.max,
+                    cel.sta.mdn,
+                    cel.sta.mean,
+                    cel.sta.std,
+                    cel.sta.p5,
+                    cel.sta.p95,
+                    cel.sta.mad,
+                    cel.sta.ci_lo,
+                    cel.sta.ci_hi,
+                    cel.ovh.mdn,
+                    cel.ovh.nse,
+                    cel.cor.mdn,
+                    cel.cor.ci_lo,
+                    cel.cor.ci_hi,
+                    ns,
+                    cel.unr,
+                    cel.bat,
+                    cel.itr,
+                    ctrs.join(","),
+                    smps.join(" ")
+                );
+            }
+        }
+        ret
+    }
+}
+
+/// Returns the batch factor of results saved before batching.
+fn bat_dft() -> u32 {
+    1
+}
+
+impl Mta {
+    /// Returns metadata of the current build and machine.
+    pub fn new(itr: u16, seed: u64, adp: Option<Adp>) -> Self {
+        Self {
+            tme: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|x| x.as_secs())
+                .unwrap_or_default(),
+            git: option_env!("MTR_GIT").unwrap_or("unknown").to_string(),
+            rustc: option_env!("MTR_RUSTC").unwrap_or("unknown").to_string(),
+            cpu: cpu_nam(),
+            itr,
+            seed,
+            bld_seed: option_env!("MTR_BLD_SEED")
+                .and_then(|x| x.parse().ok())
+                .unwrap_or_default(),
+            afn: afn(),
+            wkr_afn: wkr_cpus(),
+            adp,
+            frq: Some(*frq()),
+        }
+    }
+}
+
+/// Returns the CPU model name.
+pub fn cpu_nam() -> String {
+    fs::read_to_string("/proc/cpuinfo")
+        .ok()
+        .and_then(|txt| {
+            txt.lines()
+                .find(|lne| lne.starts_with("model name"))
+                .and_then(|lne| lne.split_once(':'))
+                .map(|(_, nam)| nam.trim().to_string())
+        })
+        .unwrap_or_else(|| String::from("unknown"))
+}
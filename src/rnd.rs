@@ -0,0 +1,29 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Seed of runtime random generators when `--seed` isn't given.
+pub const SEED_DFT: u64 = 0;
+
+/// Seed of the run.
+static SEED: AtomicU64 = AtomicU64::new(SEED_DFT);
+
+/// Sets the seed of runtime random generators.
+pub fn set_seed(seed: u64) {
+    SEED.store(seed, Ordering::Relaxed);
+}
+
+/// Returns the run seed.
+pub fn seed() -> u64 {
+    SEED.load(Ordering::Relaxed)
+}
+
+/// Returns a random generator seeded from the run seed and a length.
+///
+/// Used in place of `thread_rng()` so inputs, such as shuffled indexes, are reproducible.
+/// Like the literals of `MTR_SEED`, every call for a length repeats the same sequence, so
+/// compared variants read equal inputs, and an input doesn't depend on which series ran
+/// before it or how many samples they drew.
+pub fn new_rng(len: usize) -> StdRng {
+    StdRng::seed_from_u64(seed() ^ (len as u64).rotate_left(32))
+}
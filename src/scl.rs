@@ -0,0 +1,174 @@
+use crate::*;
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::Table;
+
+/// Name of the struct label holding a thread count.
+pub const THD: &str = "thd";
+
+/// Returns the thread count of a label set, when it has one.
+pub fn thd_cnt<L: Label>(lbls: &[L]) -> Option<u32> {
+    lbls.iter()
+        .find(|lbl| lbl_mat(*lbl, THD))
+        .and_then(|lbl| lbl.val().ok())
+}
+
+/// Returns scaling tables of parallel series against their single thread series.
+///
+/// A single thread series, such as `acm(1),unr(1),thd(1)`, is the baseline of every series
+/// with more threads and all of its other labels. Parallel series are grouped by their
+/// extra labels, such as `mpsc`, into one table per baseline and group.
+pub fn scl_tbls<L: Label>(ress: &[&SerRes<L>]) -> Vec<Table> {
+    let mut ret: Vec<Table> = Vec::new();
+    for bas in ress.iter().filter(|res| thd_cnt(&res.lbls) == Some(1)) {
+        let bas_lbls: Vec<L> = bas
+            .lbls
+            .iter()
+            .filter(|lbl| !lbl_mat(*lbl, THD))
+            .copied()
+            .collect();
+        // Parallel series by the labels they add to the baseline.
+        let mut grps: Vec<(String, Vec<&SerRes<L>>)> = Vec::new();
+        for res in ress
+            .iter()
+            .filter(|res| thd_cnt(&res.lbls).is_some_and(|cnt| cnt > 1))
+        {
+            if !bas_lbls.iter().all(|lbl| res.lbls.contains(lbl)) {
+                continue;
+            }
+            let ext: Vec<L> = res
+                .lbls
+                .iter()
+                .filter(|lbl| !lbl_mat(*lbl, THD) && !bas_lbls.contains(lbl))
+                .copied()
+                .collect();
+            let ext = lbls_str(&ext);
+            match grps.iter_mut().find(|(key, _)| *key == ext) {
+                Some((_, grp)) => grp.push(res),
+                None => grps.push((ext, vec![res])),
+            }
+        }
+        for (_, grp) in grps.iter_mut() {
+            grp.sort_by_key(|res| thd_cnt(&res.lbls));
+            ret.push(scl_tbl(bas, grp));
+        }
+    }
+    ret
+}
+
+/// Returns a scaling table of parallel series against a single thread series.
+///
+/// Each cell shows the speedup over one thread above the parallel efficiency, the speedup
+/// per thread, from corrected medians. Speedups whose bootstrap interval lies above one
+/// are marked `*`. The last column is the smallest struct label from which a thread count
+/// beats one thread at every larger label. The last rows fit the serial fraction of each
+/// column to Amdahl's law, `1 / (s + (1 - s) / n)`, and to Gustafson's law, `n - s (n - 1)`,
+/// by least squares across thread counts.
+pub fn scl_tbl<L: Label>(bas: &SerRes<L>, pars: &[&SerRes<L>]) -> Table {
+    let mut tbl = Table::new();
+    tbl.load_preset(UTF8_FULL);
+
+    // Header: struct label values of the baseline.
+    let cel_lbls: Vec<L> = bas.cels.iter().map(|(lbl, _)| *lbl).collect();
+    let nam = cel_lbls
+        .first()
+        .map(|lbl| format!("{:#}", lbl))
+        .unwrap_or_default();
+    let mut hdr = vec![format!("{} vs {}", nam, lbls_str(&bas.lbls))];
+    hdr.extend(
+        cel_lbls
+            .iter()
+            .map(|lbl| fmt_cnt(lbl.val().unwrap_or_default() as u64)),
+    );
+    hdr.push(String::from("beats from"));
+    tbl.set_header(hdr);
+
+    let cor = |res: &SerRes<L>, cel_lbl: L| -> Option<Vec<f64>> {
+        let (lbl, smps) = res.cels.iter().find(|(lbl, _)| *lbl == cel_lbl)?;
+        let ovh = res.ovh(*lbl);
+        let cor = ovh.cor(smps);
+        (!ovh.is_unr(mdn(&cor))).then_some(cor)
+    };
+
+    // Speedup per thread count and column, when both medians are reliable.
+    let mut spds: Vec<(u32, Vec<Option<f64>>)> = Vec::with_capacity(pars.len());
+    for par in pars.iter() {
+        let cnt = thd_cnt(&par.lbls).unwrap_or(1);
+        let mut cels = vec![lbls_str(&par.lbls)];
+        let mut spd_row: Vec<Option<f64>> = Vec::with_capacity(cel_lbls.len());
+        let mut bts: Vec<bool> = Vec::with_capacity(cel_lbls.len());
+        for cel_lbl in cel_lbls.iter() {
+            let (Some(cor_bas), Some(cor_par)) = (cor(bas, *cel_lbl), cor(par, *cel_lbl)) else {
+                cels.push(String::from("-"));
+                spd_row.push(None);
+                bts.push(false);
+                continue;
+            };
+            let spd = mdn(&cor_bas) / mdn(&cor_par).max(CYC_MIN);
+            let (lo, _) = rto_ci(&cor_bas, &cor_par);
+            let mrk = if lo > 1.0 { " *" } else { "" };
+            cels.push(format!(
+                "{}x{}\n{:.0}%",
+                fmt_dec(spd),
+                mrk,
+                spd / cnt as f64 * 100.0
+            ));
+            spd_row.push(Some(spd));
+            bts.push(lo > 1.0);
+        }
+        // The smallest label from which every larger label beats one thread.
+        let frm = bts.iter().rposition(|bt| !bt).map_or(0, |idx| idx + 1);
+        cels.push(match cel_lbls.get(frm) {
+            Some(lbl) => fmt_cnt(lbl.val().unwrap_or_default() as u64),
+            None => String::from("never"),
+        });
+        tbl.add_row(cels);
+        spds.push((cnt, spd_row));
+    }
+
+    // Serial fractions per column across thread counts.
+    let mut row = |nam: &str, f: fn(&[(f64, f64)]) -> f64| {
+        let mut cels = vec![String::from(nam)];
+        cels.extend((0..cel_lbls.len()).map(|col| {
+            let pts: Vec<(f64, f64)> = spds
+                .iter()
+                .filter_map(|(cnt, row)| row[col].map(|spd| (*cnt as f64, spd)))
+                .collect();
+            match f(&pts) {
+                _ if pts.is_empty() => String::from("-"),
+                // Fractions above one mean threads are slower than one thread.
+                frc if frc > 1.0 => String::from("> 1"),
+                frc => format!("{:.2}", frc),
+            }
+        }));
+        cels.push(String::new());
+        tbl.add_row(cels);
+    };
+    row("serial (Amdahl)", amdahl);
+    row("serial (Gustafson)", gustafson);
+
+    tbl
+}
+
+/// Returns the serial fraction `s` fitting speedups `(n, S)` to Amdahl's law by least
+/// squares on `1 / S = s + (1 - s) / n`.
+///
+/// Above one when the parallel series is slower than one thread.
+pub fn amdahl(pts: &[(f64, f64)]) -> f64 {
+    let (num, den) = pts.iter().fold((0.0, 0.0), |(num, den), (cnt, spd)| {
+        let inv = 1.0 / cnt;
+        (
+            num + (1.0 / spd - inv) * (1.0 - inv),
+            den + (1.0 - inv).powi(2),
+        )
+    });
+    num / den
+}
+
+/// Returns the serial fraction `s` fitting speedups `(n, S)` to Gustafson's law by least
+/// squares on `S = n - s (n - 1)`.
+pub fn gustafson(pts: &[(f64, f64)]) -> f64 {
+    let (num, den) = pts.iter().fold((0.0, 0.0), |(num, den), (cnt, spd)| {
+        (num + (cnt - spd) * (cnt - 1.0), den + (cnt - 1.0).powi(2))
+    });
+    num / den
+}
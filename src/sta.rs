@@ -0,0 +1,158 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+/// Number of bootstrap resamples.
+pub const BTS_CNT: usize = 1000;
+
+/// Confidence level of bootstrap intervals.
+pub const CNF: f64 = 0.95;
+
+/// Seed of bootstrap resampling, fixed so intervals are reproducible.
+pub const BTS_SEED: u64 = 0;
+
+/// Smallest cycles a ratio divides by, keeping ratios to a corrected median of zero finite.
+pub const CYC_MIN: f64 = 0.001;
+
+/// Summary statistics of cycle samples.
+///
+/// Samples are cycles per invocation, fractional when a batch spreads a window across
+/// invocations.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Sta {
+    /// Minimum cycles.
+    pub min: f64,
+    /// Maximum cycles.
+    pub max: f64,
+    /// Median cycles.
+    pub mdn: f64,
+    /// Mean cycles.
+    pub mean: f64,
+    /// Sample standard deviation of cycles.
+    pub std: f64,
+    /// 5th percentile of cycles.
+    #[serde(default)]
+    pub p5: f64,
+    /// 95th percentile of cycles.
+    #[serde(default)]
+    pub p95: f64,
+    /// Median absolute deviation from the median.
+    #[serde(default)]
+    pub mad: f64,
+    /// Lower bound of the bootstrap confidence interval of the median.
+    #[serde(default)]
+    pub ci_lo: f64,
+    /// Upper bound of the bootstrap confidence interval of the median.
+    #[serde(default)]
+    pub ci_hi: f64,
+}
+
+impl Sta {
+    /// Returns summary statistics of samples.
+    pub fn new(smps: &[f64]) -> Self {
+        if smps.is_empty() {
+            return Self::default();
+        }
+        let mean = smps.iter().sum::<f64>() / smps.len() as f64;
+        let std = if smps.len() > 1 {
+            let var =
+                smps.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (smps.len() - 1) as f64;
+            var.sqrt()
+        } else {
+            0.0
+        };
+        let mut srt = smps.to_vec();
+        srt.sort_unstable_by(f64::total_cmp);
+        let mdn = mdn(&srt);
+        let (ci_lo, ci_hi) = mdn_ci(smps);
+        Self {
+            min: srt[0],
+            max: srt[srt.len() - 1],
+            mdn,
+            mean,
+            std,
+            p5: pct(&srt, 0.05),
+            p95: pct(&srt, 0.95),
+            mad: mad(smps, mdn),
+            ci_lo,
+            ci_hi,
+        }
+    }
+
+    /// Returns true when the confidence intervals of two statistics overlap.
+    pub fn ci_ovr(&self, oth: &Sta) -> bool {
+        self.ci_lo <= oth.ci_hi && oth.ci_lo <= self.ci_hi
+    }
+}
+
+/// Returns the median of samples.
+pub fn mdn(smps: &[f64]) -> f64 {
+    if smps.is_empty() {
+        return 0.0;
+    }
+    let mut smps = smps.to_vec();
+    smps.sort_unstable_by(f64::total_cmp);
+    let mid = smps.len() / 2;
+    if smps.len().is_multiple_of(2) {
+        (smps[mid - 1] + smps[mid]) / 2.0
+    } else {
+        smps[mid]
+    }
+}
+
+/// Returns a percentile of sorted samples by nearest rank, with `p` in `[0, 1]`.
+pub fn pct(srt: &[f64], p: f64) -> f64 {
+    if srt.is_empty() {
+        return 0.0;
+    }
+    let idx = (p * (srt.len() - 1) as f64).round() as usize;
+    srt[idx.min(srt.len() - 1)]
+}
+
+/// Returns the median absolute deviation of samples from their median.
+pub fn mad(smps: &[f64], mdn_smps: f64) -> f64 {
+    let dvs: Vec<f64> = smps.iter().map(|x| (x - mdn_smps).abs()).collect();
+    mdn(&dvs)
+}
+
+/// Returns the bootstrap confidence interval of the median of samples.
+pub fn mdn_ci(smps: &[f64]) -> (f64, f64) {
+    let mut rng = StdRng::seed_from_u64(BTS_SEED);
+    let mut mdns = bts_mdns(smps, &mut rng);
+    mdns.sort_unstable_by(f64::total_cmp);
+    (pct(&mdns, (1.0 - CNF) / 2.0), pct(&mdns, (1.0 + CNF) / 2.0))
+}
+
+/// Returns the bootstrap confidence interval of the ratio of two medians, `num / den`.
+pub fn rto_ci(num: &[f64], den: &[f64]) -> (f64, f64) {
+    let mut rng = StdRng::seed_from_u64(BTS_SEED);
+    let mdns_num = bts_mdns(num, &mut rng);
+    let mdns_den = bts_mdns(den, &mut rng);
+    let mut rtos: Vec<f64> = mdns_num
+        .iter()
+        .zip(mdns_den.iter())
+        .map(|(num, den)| num / den.max(CYC_MIN))
+        .collect();
+    if rtos.is_empty() {
+        return (0.0, 0.0);
+    }
+    rtos.sort_unstable_by(f64::total_cmp);
+    let idx = |p: f64| (p * (rtos.len() - 1) as f64).round() as usize;
+    (rtos[idx((1.0 - CNF) / 2.0)], rtos[idx((1.0 + CNF) / 2.0)])
+}
+
+/// Returns the medians of bootstrap resamples of samples.
+fn bts_mdns(smps: &[f64], rng: &mut StdRng) -> Vec<f64> {
+    if smps.is_empty() {
+        return Vec::new();
+    }
+    let mut rsm = vec![0.0; smps.len()];
+    (0..BTS_CNT)
+        .map(|_| {
+            for x in rsm.iter_mut() {
+                *x = smps[rng.gen_range(0..smps.len())];
+            }
+            mdn(&rsm)
+        })
+        .collect()
+}
@@ -0,0 +1,473 @@
+use crate::*;
+use anyhow::{bail, Result};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::hint::black_box;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// A benchmark function which is timed with a timer, given a batch factor.
+pub type Fun = Box<dyn Fn(&RefCell<Tme>, u32)>;
+
+/// A benchmark function which runs untimed and returns its output.
+pub type Chk = Box<dyn Fn() -> Out>;
+
+/// How a benchmark function was inserted, which decides its timer overhead.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Knd {
+    /// Timed from start to end with `Ser::ins`.
+    Ins,
+    /// Starts and stops its own timer with `Ser::ins_prm`.
+    InsPrm,
+}
+
+/// A study of benchmark functions grouped by label set.
+pub struct Stdy<L: Label> {
+    /// Registered series of benchmark functions.
+    pub sers: Vec<Ser<L>>,
+}
+
+/// A series of benchmark functions sharing a label set.
+pub struct Ser<L: Label> {
+    /// Labels identifying the series.
+    pub lbls: Vec<L>,
+    /// Benchmark functions keyed by a struct label, such as `Len(16)`.
+    pub funs: Vec<(L, Knd, Fun)>,
+    /// Output checks keyed by the struct label of each benchmark function.
+    pub chks: Vec<(L, Chk)>,
+    /// Bytes read per element, when the series walks data of its length.
+    pub elm: Option<u32>,
+    /// Whether timed functions spawn threads, which inherit the CPUs of the measuring thread.
+    pub spn: bool,
+}
+
+/// Measured cycles of one series.
+#[derive(Debug, Clone)]
+pub struct SerRes<L: Label> {
+    /// Labels identifying the series.
+    pub lbls: Vec<L>,
+    /// Cycle samples per struct label.
+    pub cels: Vec<(L, Vec<f64>)>,
+    /// Hardware counter samples per struct label, empty when not counting.
+    pub ctrs: Vec<(L, Vec<Ctrs>)>,
+    /// Calibrated timer overhead per invocation per struct label.
+    pub ovhs: Vec<(L, Ovh)>,
+    /// Batch factor per struct label.
+    pub bats: Vec<(L, u32)>,
+    /// Bytes read per element, when the series walks data of its length.
+    pub elm: Option<u32>,
+}
+
+impl<L: Label> SerRes<L> {
+    /// Returns the timer overhead of a struct label.
+    pub fn ovh(&self, lbl: L) -> Ovh {
+        self.ovhs
+            .iter()
+            .find(|(lbl_ovh, _)| *lbl_ovh == lbl)
+            .map(|(_, ovh)| *ovh)
+            .unwrap_or_default()
+    }
+}
+
+impl<L: Label> Stdy<L> {
+    /// Returns a new study.
+    pub fn new() -> Self {
+        Self { sers: Vec::new() }
+    }
+
+    /// Registers a series of benchmark functions with a builder.
+    pub fn reg_bld<F>(&mut self, lbls: &[L], bld: F)
+    where
+        F: FnOnce(&mut Ser<L>),
+    {
+        let mut lbls = lbls.to_vec();
+        lbls.sort();
+        let mut ser = Ser {
+            lbls,
+            funs: Vec::new(),
+            chks: Vec::new(),
+            elm: None,
+            spn: false,
+        };
+        bld(&mut ser);
+        self.sers.push(ser);
+    }
+
+    /// Returns the registered label sets.
+    pub fn lbl_sets(&self) -> Vec<&[L]> {
+        self.sers.iter().map(|ser| ser.lbls.as_slice()).collect()
+    }
+
+    /// Prints the registered label sets.
+    pub fn lst(&self) {
+        for ser in self.sers.iter() {
+            println!("{}", lbls_str(&ser.lbls));
+        }
+    }
+
+    /// Returns the ids of series matching a selection.
+    pub fn sel_ids(&self, sel: &[String]) -> Vec<usize> {
+        self.sers
+            .iter()
+            .enumerate()
+            .filter(|(_, ser)| sel_mat(sel, &ser.lbls))
+            .map(|(id, _)| id)
+            .collect()
+    }
+
+    /// Runs the queried series and prints a table for each comparison, ranking and best
+    /// report.
+    ///
+    /// Selections which aren't compared, ranked or reported are printed in a table of their
+    /// own. With an adaptive stopping rule, `itr` is the minimum iterations per benchmark
+    /// function. Returns the results of each series which ran.
+    pub fn run(&mut self, qry: QryBld, itr: u16, adp: Option<Adp>) -> Result<Vec<SerRes<L>>> {
+        if itr == 0 {
+            bail!("iteration count must be greater than zero");
+        }
+
+        // Resolve each selection to series ids.
+        let mut sel_ids: Vec<Vec<usize>> = Vec::with_capacity(qry.sels.len());
+        for sel in qry.sels.iter() {
+            let ids = self.sel_ids(sel);
+            if ids.is_empty() {
+                bail!("no label set matches '{}'", sel.join(","));
+            }
+            sel_ids.push(ids);
+        }
+
+        // Group series into tables.
+        let mut tbls: Vec<Vec<usize>> = Vec::new();
+        for (sel_a, sel_b) in qry.cmps.iter() {
+            if *sel_a >= sel_ids.len() || *sel_b >= sel_ids.len() {
+                bail!("comparison references an unknown selection id");
+            }
+            let mut ids = sel_ids[*sel_a].clone();
+            ids.extend(sel_ids[*sel_b].iter());
+            tbls.push(ids);
+        }
+        let mut rnks: Vec<Vec<usize>> = Vec::with_capacity(qry.rnks.len());
+        for sels in qry.rnks.iter() {
+            if sels.iter().any(|sel| *sel >= sel_ids.len()) {
+                bail!("ranking references an unknown selection id");
+            }
+            rnks.push(
+                sels.iter()
+                    .flat_map(|sel| sel_ids[*sel].iter().copied())
+                    .collect(),
+            );
+        }
+        let mut bsts: Vec<Vec<usize>> = Vec::with_capacity(qry.bsts.len());
+        for sel in qry.bsts.iter() {
+            match sel_ids.get(*sel) {
+                Some(ids) => bsts.push(ids.clone()),
+                None => bail!("best report references an unknown selection id"),
+            }
+        }
+        for (id, ids) in sel_ids.iter().enumerate() {
+            if !qry.cmps.iter().any(|(a, b)| *a == id || *b == id)
+                && !qry.rnks.iter().any(|sels| sels.contains(&id))
+                && !qry.bsts.contains(&id)
+            {
+                tbls.push(ids.clone());
+            }
+        }
+
+        // Check compared, ranked and swept variants compute the same outputs before timing them.
+        let mut outs: BTreeMap<(usize, L), Out> = BTreeMap::new();
+        for (sel_a, sel_b) in qry.cmps.iter() {
+            let mut ids = sel_ids[*sel_a].clone();
+            ids.extend(sel_ids[*sel_b].iter());
+            self.chk(&ids, &mut outs)?;
+        }
+        for ids in rnks.iter().chain(bsts.iter()) {
+            self.chk(ids, &mut outs)?;
+        }
+
+        // Calibrate the timer overhead and counter frequency before any series runs.
+        let cal = Cal::run();
+        println!("{}", cal);
+        let frq = frq();
+        println!("{}", frq);
+        if !frq.inv {
+            eprintln!(
+                "warning: the TSC lacks constant_tsc or nonstop_tsc, so cycles and nanoseconds \
+                 drift with frequency scaling and sleep states"
+            );
+        }
+
+        // Threads spawned while timing inherit the pinned CPU and `--wkr-cpus` doesn't reach
+        // them, so parallel series which spawn them run their threads in turn.
+        if let Some(cpu) = msr_cpu() {
+            let mut ids: Vec<usize> = tbls
+                .iter()
+                .chain(rnks.iter())
+                .chain(bsts.iter())
+                .flatten()
+                .copied()
+                .collect();
+            ids.sort();
+            ids.dedup();
+            for id in ids {
+                let ser = &self.sers[id];
+                if ser.spn && thd_cnt(&ser.lbls).is_some_and(|cnt| cnt > 1) {
+                    eprintln!(
+                        "warning: {} spawns threads while timed, which inherit CPU {} from \
+                         --cpu and run on it in turn",
+                        lbls_str(&ser.lbls),
+                        cpu
+                    );
+                }
+            }
+        }
+
+        // Run each series once, even when it's in several tables.
+        let mut ress: BTreeMap<usize, SerRes<L>> = BTreeMap::new();
+        for tbl in tbls
+            .iter_mut()
+            .chain(rnks.iter_mut())
+            .chain(bsts.iter_mut())
+        {
+            tbl.sort_by(|a, b| self.sers[*a].lbls.cmp(&self.sers[*b].lbls));
+            tbl.dedup();
+            for id in tbl.iter() {
+                if !ress.contains_key(id) {
+                    ress.insert(*id, self.sers[*id].run(itr, &cal, adp));
+                }
+            }
+        }
+
+        for tbl in tbls.iter() {
+            let tbl_ress: Vec<&SerRes<L>> = tbl.iter().map(|id| &ress[id]).collect();
+            println!("{}", cmp_tbl(&tbl_ress));
+        }
+        for rnk in rnks.iter() {
+            let rnk_ress: Vec<&SerRes<L>> = rnk.iter().map(|id| &ress[id]).collect();
+            println!("{}", rnk_tbl(&rnk_ress));
+        }
+        for bst in bsts.iter() {
+            let bst_ress: Vec<&SerRes<L>> = bst.iter().map(|id| &ress[id]).collect();
+            println!("{}", bst_tbl(&bst_ress));
+        }
+
+        // Scale parallel series against their single thread series, and fit the cost of
+        // each series over its struct label.
+        let all_ress: Vec<&SerRes<L>> = ress.values().collect();
+        for tbl in scl_tbls(&all_ress) {
+            println!("{}", tbl);
+        }
+        println!("{}", fit_tbl(&all_ress));
+
+        // Report where the faster series of each compared pair changes.
+        for (sel_a, sel_b) in qry.cmps.iter() {
+            for id_a in sel_ids[*sel_a].iter() {
+                for id_b in sel_ids[*sel_b].iter().filter(|id_b| *id_b != id_a) {
+                    for xov in xovs(&ress[id_a], &ress[id_b]) {
+                        println!("crossover: {}", xov);
+                    }
+                }
+            }
+        }
+
+        // Warn about series which don't scale with their length.
+        for res in ress.values() {
+            if let Some(wrn) = dce_wrn(res) {
+                eprintln!("warning: {}", wrn);
+            }
+        }
+
+        Ok(ress.into_values().collect())
+    }
+
+    /// Checks that series return equal outputs for each struct label they share.
+    ///
+    /// Each benchmark function runs once, and every variant reads the same shuffled input,
+    /// drawn by `new_rng` for its length. Outputs are cached across calls.
+    pub fn chk(&self, ids: &[usize], outs: &mut BTreeMap<(usize, L), Out>) -> Result<()> {
+        // The first series with a struct label is the reference of that label.
+        let mut refs: BTreeMap<L, usize> = BTreeMap::new();
+        for id in ids.iter() {
+            for (lbl, chk) in self.sers[*id].chks.iter() {
+                outs.entry((*id, *lbl)).or_insert_with(chk);
+                let id_ref = *refs.entry(*lbl).or_insert(*id);
+                let out = &outs[&(*id, *lbl)];
+                let out_ref = &outs[&(id_ref, *lbl)];
+                if out != out_ref {
+                    bail!(
+                        "outputs differ at {}: '{}' returned {}, '{}' returned {}",
+                        lbl,
+                        lbls_str(&self.sers[id_ref].lbls),
+                        out_ref,
+                        lbls_str(&self.sers[*id].lbls),
+                        out
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<L: Label> Default for Stdy<L> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<L: Label> Ser<L> {
+    /// Sets the bytes read per element, which adds per element and throughput rows to
+    /// tables of the series.
+    pub fn elm(&mut self, byts: u32) {
+        self.elm = Some(byts);
+    }
+
+    /// Marks the series as spawning threads inside its timed functions.
+    pub fn spn(&mut self) {
+        self.spn = true;
+    }
+
+    /// Inserts a benchmark function which is timed from start to end.
+    pub fn ins<T, F>(&mut self, lbl: L, f: F)
+    where
+        T: Debug,
+        F: Fn() -> T + 'static,
+    {
+        let f = Rc::new(f);
+        let f_chk = f.clone();
+        self.chks.push((lbl, Box::new(move || Out::new(&f_chk()))));
+        self.funs.push((lbl, Knd::Ins, ins_fun(f)));
+    }
+
+    /// Inserts a benchmark function which starts and stops its own timer.
+    ///
+    /// Setup before `tme.borrow_mut().start()` is excluded from the measurement.
+    pub fn ins_prm<T, F>(&mut self, lbl: L, f: F)
+    where
+        T: Debug,
+        F: Fn(&RefCell<Tme>) -> T + 'static,
+    {
+        let f = Rc::new(f);
+        let f_chk = f.clone();
+        self.chks.push((
+            lbl,
+            Box::new(move || Out::new(&f_chk(&RefCell::new(Tme::default())))),
+        ));
+        self.funs.push((lbl, Knd::InsPrm, ins_prm_fun(f)));
+    }
+
+    /// Runs each benchmark function a number of iterations.
+    ///
+    /// Functions too short to resolve are batched, and samples are per invocation. Records
+    /// the calibrated timer overhead and batch factor of each function with the samples.
+    /// Hardware counts of series with more than one thread are left out, as counters only
+    /// follow the measuring thread and would miss the work of its workers.
+    ///
+    /// With an adaptive stopping rule, each function keeps sampling after `itr` iterations
+    /// until its corrected median converges or its share of the time budget runs out. The
+    /// remaining budget is split evenly across the remaining functions, so time left by a
+    /// function which converges early goes to the ones after it. Convergence is checked after
+    /// each round, which grows the samples by half, and a check is skipped, ending sampling,
+    /// when the last one scaled to the current samples wouldn't fit in the share left.
+    pub fn run(&self, itr: u16, cal: &Cal, adp: Option<Adp>) -> SerRes<L> {
+        let mut cels: Vec<(L, Vec<f64>)> = Vec::with_capacity(self.funs.len());
+        let mut ctrs: Vec<(L, Vec<Ctrs>)> = Vec::new();
+        let mut ovhs: Vec<(L, Ovh)> = Vec::with_capacity(self.funs.len());
+        let mut bats: Vec<(L, u32)> = Vec::with_capacity(self.funs.len());
+        let pll = thd_cnt(&self.lbls).is_some_and(|cnt| cnt > 1);
+        let bgn = Instant::now();
+        for (idx, (lbl, knd, f)) in self.funs.iter().enumerate() {
+            let tme = RefCell::new(Tme::new());
+            // Warm up caches and branch predictors.
+            f(&tme, 1);
+            let ovh = cal.ovh(*knd);
+            let bat = bat_fct(*knd, ovh, &tme, f);
+            let mut smps: Vec<f64> = Vec::with_capacity(itr as usize);
+            let mut smps_ctr: Vec<Ctrs> = Vec::new();
+            let ovh = ovh.bat(*knd, bat);
+            // Share of the time budget left to this function when sampling adaptively.
+            let dln = adp.map(|adp| {
+                let elp = bgn.elapsed();
+                elp.saturating_add(adp.bgt().saturating_sub(elp) / (self.funs.len() - idx) as u32)
+            });
+            // Seconds per sample of the last convergence check, whose bootstrap grows with the
+            // samples and is charged to the budget before each check.
+            let mut chk_smp: f64 = 0.0;
+            let mut cnt = itr as usize;
+            loop {
+                for _ in 0..cnt {
+                    // Past the minimum iterations, stop at the deadline mid-round.
+                    if smps.len() >= itr as usize && dln.is_some_and(|dln| bgn.elapsed() >= dln) {
+                        break;
+                    }
+                    let (elp, ctr) = bat_smp(bat, &tme, f);
+                    smps.push(elp);
+                    if let Some(ctr) = ctr {
+                        smps_ctr.push(ctr);
+                    }
+                }
+                let (Some(adp), Some(dln)) = (adp, dln) else {
+                    break;
+                };
+                let chk = Duration::from_secs_f64(chk_smp * smps.len() as f64);
+                if smps.len() >= ITR_MAX || bgn.elapsed() + chk >= dln {
+                    break;
+                }
+                let bgn_chk = Instant::now();
+                if adp.cnv(&ovh.cor(&smps)) {
+                    break;
+                }
+                chk_smp = bgn_chk.elapsed().as_secs_f64() / smps.len() as f64;
+                // Grow by half each round, so the bootstrap check stays a small share.
+                cnt = (smps.len() / 2).clamp(1, ITR_MAX - smps.len());
+            }
+            cels.push((*lbl, smps));
+            if !pll && !smps_ctr.is_empty() {
+                ctrs.push((*lbl, smps_ctr));
+            }
+            ovhs.push((*lbl, ovh));
+            bats.push((*lbl, bat));
+        }
+
+        SerRes {
+            lbls: self.lbls.clone(),
+            cels,
+            ctrs,
+            ovhs,
+            bats,
+            elm: self.elm,
+        }
+    }
+}
+
+/// Returns a timed function of a closure which is timed from start to end.
+///
+/// The closure runs a batch factor of times inside one timed window. Results are kept until
+/// the timer stops, so dropping them, such as freeing a `Vec`, isn't timed.
+pub fn ins_fun<T, F>(f: Rc<F>) -> Fun
+where
+    F: Fn() -> T + 'static,
+{
+    Box::new(move |tme: &RefCell<Tme>, bat: u32| {
+        let mut rets: Vec<T> = Vec::with_capacity(bat as usize);
+        tme.borrow_mut().start();
+        for _ in 0..bat {
+            rets.push(f());
+        }
+        tme.borrow_mut().stop();
+        black_box(rets);
+    })
+}
+
+/// Returns a timed function of a closure which starts and stops its own timer.
+///
+/// Such functions always run with a batch factor of 1, as setup inside the closure can't
+/// be repeated untimed.
+pub fn ins_prm_fun<T, F>(f: Rc<F>) -> Fun
+where
+    F: Fn(&RefCell<Tme>) -> T + 'static,
+{
+    Box::new(move |tme: &RefCell<Tme>, _bat: u32| {
+        black_box(f(tme));
+    })
+}
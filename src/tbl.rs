@@ -0,0 +1,556 @@
+use crate::*;
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::Table;
+
+/// Returns a table comparing the median cycles of series.
+///
+/// Columns are the struct labels of any series, such as `Len(16)`, and the last row is the
+/// ratio of the slowest to the fastest series in each column. Cells a series lacks show `-`.
+/// Each cell shows the median above its bootstrap confidence interval, both less the
+/// calibrated timer overhead, in the units set with `set_unt`. Cells below the noise floor of
+/// the timer are marked with `?`. The fastest cell of a column is marked with `*` when it's
+/// above the noise floor and its interval doesn't overlap the interval of any other series.
+/// Series which walk data of their length gain rows of cycles per element and throughput.
+/// When cells ran different numbers of iterations, as with adaptive sampling, each series
+/// gains a row of its iteration counts.
+pub fn cmp_tbl<L: Label>(ress: &[&SerRes<L>]) -> Table {
+    let mut tbl = Table::new();
+    tbl.load_preset(UTF8_FULL);
+
+    // Header: struct label name followed by struct label values.
+    let cel_lbls = cel_lbls(ress);
+    if cel_lbls.is_empty() {
+        return tbl;
+    }
+    tbl.set_header(hdr(&cel_lbls));
+
+    let cor = Cor::new(ress, &cel_lbls);
+    let wins = cor.wins();
+    let Cor {
+        stas,
+        smps,
+        unrs,
+        has,
+    } = cor;
+
+    // Iteration counts are only worth a row when they differ.
+    let mut itrs = ress
+        .iter()
+        .flat_map(|res| res.cels.iter().map(|(_, smps)| smps.len()));
+    let itr_var = itrs.next().is_some_and(|fst| itrs.any(|itr| itr != fst));
+
+    // Rows: median cycles per series.
+    for (row, res) in ress.iter().enumerate() {
+        let mut cels = vec![lbls_str(&res.lbls)];
+        cels.extend(stas[row].iter().enumerate().map(|(col, sta)| {
+            if !has[row][col] {
+                return String::from("-");
+            }
+            let mrk = if wins[col] == Some(row) {
+                " *"
+            } else if unrs[row][col] {
+                " ?"
+            } else {
+                ""
+            };
+            fmt_cel(sta, mrk)
+        }));
+        tbl.add_row(cels);
+        elm_rows(&mut tbl, res, &cel_lbls, &stas[row], &unrs[row]);
+        ctr_rows(&mut tbl, res, &cel_lbls);
+        if itr_var {
+            itr_row(&mut tbl, res, &cel_lbls);
+        }
+    }
+
+    // Ratio: max / min per column.
+    if stas.len() > 1 {
+        let mut cels = vec![String::from("ratio (max / min)")];
+        for col in 0..cel_lbls.len() {
+            let rows: Vec<usize> = (0..stas.len()).filter(|row| has[*row][col]).collect();
+            if rows.len() < 2 {
+                cels.push(String::from("-"));
+                continue;
+            }
+            let cmp = |a: &usize, b: &usize| stas[*a][col].mdn.total_cmp(&stas[*b][col].mdn);
+            let max = rows.iter().copied().max_by(cmp).unwrap_or_default();
+            let min = rows.iter().copied().min_by(cmp).unwrap_or_default();
+            let rto = stas[max][col].mdn / stas[min][col].mdn.max(CYC_MIN);
+            let (lo, hi) = rto_ci(&smps[max][col], &smps[min][col]);
+            let mrk = if unrs[max][col] || unrs[min][col] {
+                " ?"
+            } else {
+                ""
+            };
+            cels.push(format!(
+                "{}{}\n[{}, {}]",
+                fmt_rto(rto),
+                mrk,
+                fmt_rto(lo),
+                fmt_rto(hi)
+            ));
+        }
+        tbl.add_row(cels);
+    }
+
+    tbl
+}
+
+/// Returns a table ranking any number of series per struct label.
+///
+/// Columns are struct labels, such as `Len(16)`. Each cell shows the median less the
+/// calibrated timer overhead, in the units set with `set_unt`, above the rank of the series
+/// in its column and its ratio to the fastest median. Equal medians share a rank. The
+/// fastest cell of a column is marked with `*` under the same rule as `cmp_tbl`, and cells
+/// below the noise floor are marked with `?`. Columns are the struct labels of any series,
+/// and cells a series lacks show `-` and are left out of ranks and ratios. Rows are ordered
+/// by the geometric mean of their ratios over reliable columns, shown in the last column.
+pub fn rnk_tbl<L: Label>(ress: &[&SerRes<L>]) -> Table {
+    let mut tbl = Table::new();
+    tbl.load_preset(UTF8_FULL);
+
+    let cel_lbls = cel_lbls(ress);
+    if cel_lbls.is_empty() {
+        return tbl;
+    }
+    let mut hdr = hdr(&cel_lbls);
+    hdr.push(String::from("geo mean"));
+    tbl.set_header(hdr);
+
+    let cor = Cor::new(ress, &cel_lbls);
+    let wins = cor.wins();
+    let Cor {
+        stas, unrs, has, ..
+    } = &cor;
+    let rows = |col: usize| (0..stas.len()).filter(move |row| has[*row][col]);
+
+    // Ratio to the fastest median per series and column, when the series has the column.
+    let rtos: Vec<Vec<Option<f64>>> = (0..stas.len())
+        .map(|row| {
+            (0..cel_lbls.len())
+                .map(|col| {
+                    let min = rows(col)
+                        .map(|row| stas[row][col].mdn)
+                        .fold(f64::INFINITY, f64::min);
+                    has[row][col].then(|| stas[row][col].mdn.max(CYC_MIN) / min.max(CYC_MIN))
+                })
+                .collect()
+        })
+        .collect();
+    let geos: Vec<Option<f64>> = (0..stas.len())
+        .map(|row| {
+            let lns: Vec<f64> = (0..cel_lbls.len())
+                .filter(|col| rows(*col).all(|row| !unrs[row][*col]))
+                .filter_map(|col| rtos[row][col].map(|rto| rto.ln()))
+                .collect();
+            (!lns.is_empty()).then(|| (lns.iter().sum::<f64>() / lns.len() as f64).exp())
+        })
+        .collect();
+    let mut ordr: Vec<usize> = (0..stas.len()).collect();
+    ordr.sort_by(|a, b| {
+        let geo = |row: usize| geos[row].unwrap_or(f64::INFINITY);
+        geo(*a).total_cmp(&geo(*b))
+    });
+
+    for row in ordr {
+        let mut cels = vec![lbls_str(&ress[row].lbls)];
+        cels.extend(stas[row].iter().enumerate().map(|(col, sta)| {
+            let Some(rto) = rtos[row][col] else {
+                return String::from("-");
+            };
+            let mrk = if wins[col] == Some(row) {
+                " *"
+            } else if unrs[row][col] {
+                " ?"
+            } else {
+                ""
+            };
+            let rnk = 1 + rows(col)
+                .filter(|oth| stas[*oth][col].mdn < sta.mdn)
+                .count();
+            format!("{}{}\n#{} {}x", fmt_tme(sta.mdn), mrk, rnk, fmt_rto(rto))
+        }));
+        cels.push(match geos[row] {
+            Some(geo) => format!("{}x", fmt_rto(geo)),
+            None => String::from("-"),
+        });
+        tbl.add_row(cels);
+    }
+
+    tbl
+}
+
+/// Returns a table of the fastest series per struct label, such as the best configuration
+/// of a sweep per length.
+///
+/// Series may have different struct labels. At each, the series with the lowest corrected
+/// median wins, marked `?` when its median is below the noise floor. The margin is the
+/// ratio of the runner-up's median to the winner's above its bootstrap interval, marked `*`
+/// when the interval lies above one, or `?` when either median is below the noise floor.
+pub fn bst_tbl<L: Label>(ress: &[&SerRes<L>]) -> Table {
+    let mut tbl = Table::new();
+    tbl.load_preset(UTF8_FULL);
+
+    let cel_lbls = cel_lbls(ress);
+    let Some(hdr) = hdr(&cel_lbls).into_iter().next() else {
+        return tbl;
+    };
+    tbl.set_header(vec![
+        hdr,
+        String::from("best"),
+        String::from("median"),
+        String::from("runner-up"),
+        String::from("margin"),
+    ]);
+
+    for cel_lbl in cel_lbls.iter() {
+        // Corrected samples of each series with the struct label, fastest first.
+        let mut cnds: Vec<(&SerRes<L>, Vec<f64>, f64, bool)> = ress
+            .iter()
+            .filter_map(|res| {
+                let (_, smps) = res.cels.iter().find(|(lbl, _)| lbl == cel_lbl)?;
+                let ovh = res.ovh(*cel_lbl);
+                let cor = ovh.cor(smps);
+                let mdn = mdn(&cor);
+                Some((*res, cor, mdn, ovh.is_unr(mdn)))
+            })
+            .collect();
+        cnds.sort_by(|a, b| a.2.total_cmp(&b.2));
+        let Some((bst, cor_bst, mdn_bst, unr_bst)) = cnds.first() else {
+            continue;
+        };
+        let mut cels = vec![
+            fmt_cnt(cel_lbl.val().unwrap_or_default() as u64),
+            format!(
+                "{}{}",
+                lbls_str(&bst.lbls),
+                if *unr_bst { " ?" } else { "" }
+            ),
+            fmt_tme(*mdn_bst),
+        ];
+        match cnds.get(1) {
+            Some((run, cor_run, mdn_run, unr_run)) => {
+                cels.push(lbls_str(&run.lbls));
+                let rto = fmt_rto(mdn_run.max(CYC_MIN) / mdn_bst.max(CYC_MIN));
+                if *unr_bst || *unr_run {
+                    cels.push(format!("{}x ?", rto));
+                } else {
+                    let (lo, hi) = rto_ci(cor_run, cor_bst);
+                    let mrk = if lo > 1.0 { " *" } else { "" };
+                    cels.push(format!(
+                        "{}x{}\n[{}, {}]",
+                        rto,
+                        mrk,
+                        fmt_rto(lo),
+                        fmt_rto(hi)
+                    ));
+                }
+            }
+            None => cels.extend([String::from("-"), String::from("-")]),
+        }
+        tbl.add_row(cels);
+    }
+
+    tbl
+}
+
+/// Returns the struct labels of any of the series, sorted and without duplicates.
+fn cel_lbls<L: Label>(ress: &[&SerRes<L>]) -> Vec<L> {
+    let mut ret: Vec<L> = ress
+        .iter()
+        .flat_map(|res| res.cels.iter().map(|(lbl, _)| *lbl))
+        .collect();
+    ret.sort();
+    ret.dedup();
+    ret
+}
+
+/// Returns a header of a struct label name, with the units set with `set_unt`, followed by
+/// struct label values.
+fn hdr<L: Label>(cel_lbls: &[L]) -> Vec<String> {
+    let mut ret = vec![match (cel_lbls.first(), unt()) {
+        (Some(lbl), Unt::Cyc) => format!("{:#}", lbl),
+        (Some(lbl), Unt::Ns) => format!("{:#} (ns)", lbl),
+        (Some(lbl), Unt::Both) => format!("{:#} (cycles, ns)", lbl),
+        (None, _) => String::new(),
+    }];
+    ret.extend(
+        cel_lbls
+            .iter()
+            .map(|lbl| fmt_cnt(lbl.val().unwrap_or_default() as u64)),
+    );
+    ret
+}
+
+/// Statistics and samples less the timer overhead per series and column.
+struct Cor {
+    stas: Vec<Vec<Sta>>,
+    smps: Vec<Vec<Vec<f64>>>,
+    unrs: Vec<Vec<bool>>,
+    /// Whether each series has each column; statistics of absent cells are empty.
+    has: Vec<Vec<bool>>,
+}
+
+impl Cor {
+    /// Returns corrected statistics of series per struct label, and whether each median is
+    /// below the noise floor.
+    fn new<L: Label>(ress: &[&SerRes<L>], cel_lbls: &[L]) -> Self {
+        let mut stas: Vec<Vec<Sta>> = Vec::with_capacity(ress.len());
+        let mut smps: Vec<Vec<Vec<f64>>> = Vec::with_capacity(ress.len());
+        let mut unrs: Vec<Vec<bool>> = Vec::with_capacity(ress.len());
+        let mut has: Vec<Vec<bool>> = Vec::with_capacity(ress.len());
+        for res in ress.iter() {
+            has.push(
+                cel_lbls
+                    .iter()
+                    .map(|cel_lbl| res.cels.iter().any(|(lbl, _)| lbl == cel_lbl))
+                    .collect(),
+            );
+            let row: Vec<(Vec<f64>, Ovh)> = cel_lbls
+                .iter()
+                .map(|cel_lbl| {
+                    let ovh = res.ovh(*cel_lbl);
+                    let smps = res
+                        .cels
+                        .iter()
+                        .find(|(lbl, _)| lbl == cel_lbl)
+                        .map(|(_, smps)| ovh.cor(smps))
+                        .unwrap_or_default();
+                    (smps, ovh)
+                })
+                .collect();
+            let row_stas: Vec<Sta> = row.iter().map(|(smps, _)| Sta::new(smps)).collect();
+            unrs.push(
+                row.iter()
+                    .zip(row_stas.iter())
+                    .map(|((_, ovh), sta)| ovh.is_unr(sta.mdn))
+                    .collect(),
+            );
+            stas.push(row_stas);
+            smps.push(row.into_iter().map(|(smps, _)| smps).collect());
+        }
+        Self {
+            stas,
+            smps,
+            unrs,
+            has,
+        }
+    }
+
+    /// Returns the fastest series per column, when its interval stands apart from the other
+    /// series with the column.
+    fn wins(&self) -> Vec<Option<usize>> {
+        let stas = &self.stas;
+        let cols = stas.first().map_or(0, |row| row.len());
+        (0..cols)
+            .map(|col| {
+                let rows: Vec<usize> = (0..stas.len()).filter(|row| self.has[*row][col]).collect();
+                let fst = rows
+                    .iter()
+                    .copied()
+                    .min_by(|a, b| stas[*a][col].mdn.total_cmp(&stas[*b][col].mdn))?;
+                let sep = rows
+                    .iter()
+                    .filter(|row| **row != fst)
+                    .all(|row| !stas[fst][col].ci_ovr(&stas[*row][col]));
+                (rows.len() > 1 && sep && !self.unrs[fst][col]).then_some(fst)
+            })
+            .collect()
+    }
+}
+
+/// Adds rows of cycles per element, elements per cycle, bytes per cycle and bytes per
+/// second of a series, from its corrected medians, when its element size is known.
+///
+/// Cycles per element flatten while data fits a cache, and step up where it doesn't.
+fn elm_rows<L: Label>(
+    tbl: &mut Table,
+    res: &SerRes<L>,
+    cel_lbls: &[L],
+    stas: &[Sta],
+    unrs: &[bool],
+) {
+    let Some(elm) = res.elm else {
+        return;
+    };
+    let byts = elm as f64;
+    let ghz = frq().hz / 1e9;
+    // Each row derives from the cycles per element.
+    let mut row =
+        |nam: &str, f: &dyn Fn(f64) -> f64| {
+            let mut cels = vec![String::from(nam)];
+            cels.extend(cel_lbls.iter().zip(stas.iter()).zip(unrs.iter()).map(
+                |((lbl, sta), unr)| match lbl.val().ok() {
+                    Some(len) if len != 0 && sta.mdn > 0.0 => {
+                        let val = fmt_dec(f(sta.mdn / len as f64));
+                        if *unr {
+                            format!("{} ?", val)
+                        } else {
+                            val
+                        }
+                    }
+                    _ => String::from("-"),
+                },
+            ));
+            tbl.add_row(cels);
+        };
+    row("  cycles / element", &|cpe| cpe);
+    row("  elements / cycle", &|cpe| 1.0 / cpe);
+    row("  bytes / cycle", &|cpe| byts / cpe);
+    row("  GB / s", &|cpe| byts / cpe * ghz);
+}
+
+/// Adds a row of median hardware counts per event of a series, and a row of instructions
+/// per cycle, when the series was counted.
+fn ctr_rows<L: Label>(tbl: &mut Table, res: &SerRes<L>, cel_lbls: &[L]) {
+    if res.ctrs.is_empty() {
+        return;
+    }
+    let mdns: Vec<Option<Ctrs>> = cel_lbls
+        .iter()
+        .map(|cel_lbl| {
+            res.ctrs
+                .iter()
+                .find(|(lbl, _)| lbl == cel_lbl)
+                .and_then(|(_, smps)| Ctrs::mdn(smps))
+        })
+        .collect();
+    for evt in EVTS {
+        if !mdns.iter().flatten().any(|mdn| mdn.get(evt).is_some()) {
+            continue;
+        }
+        let mut cels = vec![format!("  {}", evt.nam())];
+        cels.extend(
+            mdns.iter()
+                .map(|mdn| match mdn.and_then(|mdn| mdn.get(evt)) {
+                    Some(val) => fmt_cnt(val),
+                    None => String::from("-"),
+                }),
+        );
+        tbl.add_row(cels);
+    }
+    // Fall back to raw time-stamp cycles, which span the same window as the counters, when
+    // core cycles aren't counted.
+    let mut cels = vec![String::from("  IPC")];
+    cels.extend(mdns.iter().zip(cel_lbls.iter()).map(|(mdn_ctr, cel_lbl)| {
+        let cyc = res
+            .cels
+            .iter()
+            .find(|(lbl, _)| lbl == cel_lbl)
+            .map(|(_, smps)| mdn(smps))
+            .unwrap_or_default();
+        let ipc = mdn_ctr.and_then(|mdn_ctr| {
+            mdn_ctr
+                .ipc()
+                .or_else(|| mdn_ctr.ins.map(|ins| ins as f64 / cyc.max(CYC_MIN)))
+        });
+        match ipc {
+            Some(ipc) => format!("{:.2}", ipc),
+            None => String::from("-"),
+        }
+    }));
+    tbl.add_row(cels);
+}
+
+/// Adds a row of the iterations sampled per struct label of a series.
+fn itr_row<L: Label>(tbl: &mut Table, res: &SerRes<L>, cel_lbls: &[L]) {
+    let mut cels = vec![String::from("  iterations")];
+    cels.extend(cel_lbls.iter().map(
+        |cel_lbl| match res.cels.iter().find(|(lbl, _)| lbl == cel_lbl) {
+            Some((_, smps)) => fmt_cnt(smps.len() as u64),
+            None => String::from("-"),
+        },
+    ));
+    tbl.add_row(cels);
+}
+
+/// Returns a cell of a median and its confidence interval in the units set with `set_unt`.
+fn fmt_cel(sta: &Sta, mrk: &str) -> String {
+    let cyc = |cyc: f64| fmt_cyc(cyc);
+    let cyc = format!(
+        "{}{}\n[{}, {}]",
+        cyc(sta.mdn),
+        mrk,
+        cyc(sta.ci_lo),
+        cyc(sta.ci_hi)
+    );
+    let frq = frq();
+    let ns = |cyc: f64| fmt_ns(frq.ns(cyc));
+    let ns = format!(
+        "{}{}\n[{}, {}]",
+        ns(sta.mdn),
+        mrk,
+        ns(sta.ci_lo),
+        ns(sta.ci_hi)
+    );
+    match unt() {
+        Unt::Cyc => cyc,
+        Unt::Ns => ns,
+        Unt::Both => format!("{}\n{}", cyc, ns),
+    }
+}
+
+/// Returns cycles in the units set with `set_unt`, with nanoseconds after a slash for both.
+fn fmt_tme(cyc: f64) -> String {
+    let ns = || fmt_ns(frq().ns(cyc));
+    match unt() {
+        Unt::Cyc => fmt_cyc(cyc),
+        Unt::Ns => ns(),
+        Unt::Both => format!("{} / {}", fmt_cyc(cyc), ns()),
+    }
+}
+
+/// Returns whole cycles with thousands separators, and fractions of a batch with three
+/// significant digits.
+pub fn fmt_cyc(cyc: f64) -> String {
+    if cyc.fract() == 0.0 {
+        fmt_cnt(cyc as u64)
+    } else {
+        fmt_dec(cyc)
+    }
+}
+
+/// Returns nanoseconds with one decimal below 100, else rounded with thousands separators.
+pub fn fmt_ns(ns: f64) -> String {
+    if ns < 100.0 {
+        format!("{:.1}", ns)
+    } else {
+        fmt_cnt(ns.round() as u64)
+    }
+}
+
+/// Returns a decimal with three significant digits below 100, else rounded with thousands
+/// separators.
+pub fn fmt_dec(val: f64) -> String {
+    if val >= 100.0 {
+        return fmt_cnt(val.round() as u64);
+    }
+    if val <= 0.0 {
+        return String::from("0");
+    }
+    let prc = (2 - val.log10().floor() as i32).max(0) as usize;
+    format!("{:.*}", prc, val)
+}
+
+/// Returns a count formatted with thousands separators.
+pub fn fmt_cnt(cnt: u64) -> String {
+    let dgts = cnt.to_string();
+    let mut ret = String::with_capacity(dgts.len() + dgts.len() / 3);
+    for (idx, chr) in dgts.chars().enumerate() {
+        if idx != 0 && (dgts.len() - idx).is_multiple_of(3) {
+            ret.push(',');
+        }
+        ret.push(chr);
+    }
+    ret
+}
+
+/// Returns a ratio rounded to two significant digits.
+pub fn fmt_rto(rto: f64) -> String {
+    if rto >= 10.0 {
+        format!("{:.0}", rto)
+    } else {
+        let ret = format!("{:.1}", rto);
+        ret.trim_end_matches(".0").to_string()
+    }
+}
@@ -0,0 +1,74 @@
+use crate::*;
+
+/// Measures elapsed CPU cycles with the time-stamp counter.
+///
+/// Optionally counts hardware events over the same window.
+#[derive(Debug, Default)]
+pub struct Tme {
+    bgn: u64,
+    end: u64,
+    ctr: Option<Ctr>,
+}
+
+impl Tme {
+    /// Returns a timer with hardware counters when they're enabled with `set_ctr`.
+    pub fn new() -> Self {
+        Self {
+            ctr: if ctr_on() { Ctr::open().ok() } else { None },
+            ..Default::default()
+        }
+    }
+
+    /// Starts the measurement.
+    #[inline(always)]
+    pub fn start(&mut self) {
+        if let Some(ctr) = &self.ctr {
+            ctr.start();
+        }
+        self.bgn = tsc();
+    }
+
+    /// Stops the measurement.
+    #[inline(always)]
+    pub fn stop(&mut self) {
+        self.end = tsc();
+        if let Some(ctr) = &self.ctr {
+            ctr.stop();
+        }
+    }
+
+    /// Returns the hardware counts between start and stop, when counting.
+    pub fn ctrs(&self) -> Option<Ctrs> {
+        self.ctr.as_ref().map(|ctr| ctr.read())
+    }
+
+    /// Returns the elapsed cycles between start and stop.
+    pub fn elp(&self) -> u64 {
+        self.end.saturating_sub(self.bgn)
+    }
+}
+
+/// Reads the time-stamp counter.
+///
+/// Fences keep surrounding instructions from being reordered across the read.
+#[cfg(target_arch = "x86_64")]
+#[inline(always)]
+pub fn tsc() -> u64 {
+    use std::arch::x86_64::{_mm_lfence, _rdtsc};
+    unsafe {
+        _mm_lfence();
+        let ret = _rdtsc();
+        _mm_lfence();
+        ret
+    }
+}
+
+/// Reads a monotonic nanosecond clock on targets without a time-stamp counter.
+#[cfg(not(target_arch = "x86_64"))]
+#[inline(always)]
+pub fn tsc() -> u64 {
+    use std::sync::OnceLock;
+    use std::time::Instant;
+    static BGN: OnceLock<Instant> = OnceLock::new();
+    BGN.get_or_init(Instant::now).elapsed().as_nanos() as u64
+}
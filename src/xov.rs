@@ -0,0 +1,143 @@
+use crate::*;
+use std::fmt;
+
+/// Relative difference of medians within which two series are competitive.
+pub const XOV_TOL: f64 = 0.1;
+
+/// A change of the faster of two compared series as their struct label grows.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Xov {
+    /// Labels of the series which gains.
+    pub gan: String,
+    /// Labels of the other series.
+    pub oth: String,
+    /// What changes.
+    pub knd: XovKnd,
+    /// Name of the struct label, such as `len`.
+    pub nam: String,
+    /// Interpolated value of the struct label where the change happens.
+    pub at: f64,
+}
+
+/// Kind of a crossover.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum XovKnd {
+    /// The slower series becomes faster.
+    Ovr,
+    /// The slower series comes within `XOV_TOL` of the faster one.
+    Cmp,
+    /// One of two competitive series becomes faster.
+    Pul,
+}
+
+impl fmt::Display for Xov {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let knd = match self.knd {
+            XovKnd::Ovr => "overtakes",
+            XovKnd::Cmp => "becomes competitive with",
+            XovKnd::Pul => "pulls ahead of",
+        };
+        write!(
+            f,
+            "'{}' {} '{}' at {} ~{}",
+            self.gan,
+            knd,
+            self.oth,
+            self.nam,
+            fmt_apx(self.at)
+        )
+    }
+}
+
+/// Returns the crossovers of two series as their struct label grows.
+///
+/// At each struct label, the bootstrap interval of the ratio of corrected medians decides a
+/// faster series when it lies wholly outside `XOV_TOL` of one, and otherwise calls the
+/// series competitive. Labels where either median is below the noise floor are skipped,
+/// as are single labels whose state differs from both neighbours. Each change of state is
+/// placed by interpolating the log ratio over the log of the struct label.
+pub fn xovs<L: Label>(a: &SerRes<L>, b: &SerRes<L>) -> Vec<Xov> {
+    let bnd = (1.0 + XOV_TOL).ln();
+    let nams = [lbls_str(&a.lbls), lbls_str(&b.lbls)];
+
+    // Log ratio `a / b` and faster series per shared struct label, 1 when `b` is faster.
+    let mut pts: Vec<(L, f64, Option<usize>)> = Vec::new();
+    for (lbl, smps_a) in a.cels.iter() {
+        let Some((_, smps_b)) = b.cels.iter().find(|(lbl_b, _)| lbl_b == lbl) else {
+            continue;
+        };
+        let (ovh_a, ovh_b) = (a.ovh(*lbl), b.ovh(*lbl));
+        let (cor_a, cor_b) = (ovh_a.cor(smps_a), ovh_b.cor(smps_b));
+        let (mdn_a, mdn_b) = (mdn(&cor_a), mdn(&cor_b));
+        if ovh_a.is_unr(mdn_a) || ovh_b.is_unr(mdn_b) || lbl.val().is_err() {
+            continue;
+        }
+        let (lo, hi) = rto_ci(&cor_a, &cor_b);
+        let fst = if lo.ln() > bnd {
+            Some(1)
+        } else if hi.ln() < -bnd {
+            Some(0)
+        } else {
+            None
+        };
+        pts.push((*lbl, (mdn_a / mdn_b.max(CYC_MIN)).ln(), fst));
+    }
+
+    // Drop single labels which disagree with both neighbours, as noise.
+    let pts: Vec<(L, f64, Option<usize>)> = (0..pts.len())
+        .filter(|&idx| {
+            idx == 0 || idx == pts.len() - 1 || {
+                let (prv, nxt) = (pts[idx - 1].2, pts[idx + 1].2);
+                prv != nxt || pts[idx].2 == prv
+            }
+        })
+        .map(|idx| pts[idx])
+        .collect();
+
+    let mut ret: Vec<Xov> = Vec::new();
+    for win in pts.windows(2) {
+        let ((lbl0, y0, fst0), (lbl1, y1, fst1)) = (win[0], win[1]);
+        let (gan, knd, y) = match (fst0, fst1) {
+            (Some(fst0), Some(fst1)) if fst0 != fst1 => (fst1, XovKnd::Ovr, 0.0),
+            (Some(fst0), None) => (1 - fst0, XovKnd::Cmp, if fst0 == 0 { -bnd } else { bnd }),
+            (None, Some(fst1)) => (fst1, XovKnd::Pul, if fst1 == 0 { -bnd } else { bnd }),
+            _ => continue,
+        };
+        let (x0, x1) = (
+            (lbl0.val().unwrap_or_default() as f64).log2(),
+            (lbl1.val().unwrap_or_default() as f64).log2(),
+        );
+        let x = if y1 == y0 {
+            x0
+        } else {
+            x0 + ((y - y0) / (y1 - y0)).clamp(0.0, 1.0) * (x1 - x0)
+        };
+        ret.push(Xov {
+            gan: nams[gan].clone(),
+            oth: nams[1 - gan].clone(),
+            knd,
+            nam: format!("{:#}", lbl0),
+            at: x.exp2(),
+        });
+    }
+    ret
+}
+
+/// Returns a value rounded to two significant digits with a `k` or `M` suffix, such as
+/// `1.4k`.
+pub fn fmt_apx(val: f64) -> String {
+    let (val, sfx) = if val >= 1e6 {
+        (val / 1e6, "M")
+    } else if val >= 1e3 {
+        (val / 1e3, "k")
+    } else {
+        (val, "")
+    };
+    let val = if val >= 10.0 {
+        let scl = 10f64.powi(val.log10().floor() as i32 - 1);
+        format!("{}", (val / scl).round() * scl)
+    } else {
+        format!("{:.1}", val).trim_end_matches(".0").to_string()
+    };
+    format!("{}{}", val, sfx)
+}